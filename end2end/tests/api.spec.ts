@@ -0,0 +1,72 @@
+import { test, expect, APIRequestContext } from "@playwright/test";
+import * as fs from "fs";
+import * as path from "path";
+
+const addr = "http://localhost:3000/api/";
+// The server must run with MAILER_TRANSPORT=outbox, relative dirs are taken from its root
+const outbox = path.resolve(__dirname, "../..", process.env.MAILER_OUTBOX_DIR ?? "target/outbox");
+
+// Follows the link of the verification email, the mailer delivers it in the background
+async function verifyEmail(request: APIRequestContext, email: string) {
+  const recipient = email.replace(/[^a-zA-Z0-9]/g, "_");
+  let token: string | undefined;
+  await expect(async () => {
+    const message = fs
+      .readdirSync(outbox)
+      .filter((x) => x.endsWith("-" + recipient + ".eml"))
+      .map((x) => fs.readFileSync(path.join(outbox, x), "utf8"))
+      // Undo the quoted-printable soft line breaks and escaped equal signs
+      .map((x) => x.replace(/=\r?\n/g, "").replace(/=3D/g, "="))
+      .find((x) => x.includes("verify_email?token="));
+    token = message?.match(/verify_email\?token=([0-9a-f]+)/)?.[1];
+    expect(token).toBeTruthy();
+  }).toPass();
+  const verified = await request.get("http://localhost:3000/verify_email?token=" + token);
+  expect(await verified.text()).toContain("Your email is verified");
+}
+
+test("REST API register, article and comment flow", async ({ request }) => {
+  const username = (Math.random() + 1).toString(36).substring(7);
+
+  // Register user
+  const register = await request.post(addr + "users", {
+    data: { user: { username, email: username + "@" + username + ".com", password: username } },
+  });
+  expect(register.ok()).toBeTruthy();
+  const { user } = await register.json();
+  expect(user.username).toBe(username);
+  const headers = { Authorization: "Token " + user.token };
+
+  // Current user
+  const current = await request.get(addr + "user", { headers });
+  expect((await current.json()).user.email).toBe(username + "@" + username + ".com");
+  expect((await request.get(addr + "user")).status()).toBe(401);
+
+  // Publishing needs a verified email
+  const newArticle = { article: { title: "Api " + username, description: "From the API", body: "Body from the REST API", tagList: ["api"] } };
+  expect((await request.post(addr + "articles", { headers, data: newArticle })).status()).toBe(422);
+  await verifyEmail(request, username + "@" + username + ".com");
+
+  // Create article
+  const created = await request.post(addr + "articles", {
+    headers,
+    data: newArticle,
+  });
+  expect(created.ok()).toBeTruthy();
+  const { article } = await created.json();
+  expect(article.tagList).toEqual(["api"]);
+  expect(article.author.username).toBe(username);
+
+  // Comment it
+  const comment = await request.post(addr + "articles/" + article.slug + "/comments", {
+    headers,
+    data: { comment: { body: "Nice one" } },
+  });
+  expect(comment.ok()).toBeTruthy();
+  const comments = await request.get(addr + "articles/" + article.slug + "/comments");
+  expect((await comments.json()).comments).toHaveLength(1);
+
+  // List by author
+  const list = await request.get(addr + "articles?author=" + username);
+  expect((await list.json()).articlesCount).toBe(1);
+});
@@ -21,4 +21,15 @@ export function decodeJWT(token) {
  */
 export function emailRegex(email) {
     return /^[\w\-\.]+@([\w-]+\.)+\w{2,4}$/.test(email)
-}
\ No newline at end of file
+}
+
+/**
+ * Minutes from the local time of the browser to UTC at the given `datetime-local` value,
+ * or at the current time when it isn't a valid one.
+ * @param {string} date
+ * @returns {number}
+ */
+export function timezoneOffset(date) {
+    const offset = new Date(date).getTimezoneOffset();
+    return Number.isNaN(offset) ? new Date().getTimezoneOffset() : offset;
+}
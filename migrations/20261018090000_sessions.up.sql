@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS Sessions (
+    id bigint PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+    username text NOT NULL REFERENCES Users(username) ON DELETE CASCADE ON UPDATE CASCADE,
+    -- Only the sha256 of the refresh tokens is stored
+    refresh_token text NOT NULL UNIQUE,
+    previous_refresh_token text NULL,
+    rotated_at TIMESTAMPTZ NULL,
+    created_at TIMESTAMPTZ NOT NULL default NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ NULL
+);
+
+CREATE INDEX IF NOT EXISTS sessions_username ON Sessions (username);
+CREATE INDEX IF NOT EXISTS sessions_previous_refresh_token ON Sessions (previous_refresh_token);
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS PasswordResets (
+    id bigint PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+    username text NOT NULL REFERENCES Users(username) ON DELETE CASCADE ON UPDATE CASCADE,
+    -- Only the sha256 of the token is stored
+    token text NOT NULL UNIQUE,
+    purpose text NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL default NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ NULL
+);
+
+CREATE INDEX IF NOT EXISTS password_resets_username ON PasswordResets (username);
@@ -0,0 +1,15 @@
+CREATE TABLE IF NOT EXISTS EmailOutbox (
+    id bigint PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+    recipient text NOT NULL,
+    subject text NOT NULL,
+    text_body text NOT NULL,
+    html_body text NOT NULL,
+    attempts integer NOT NULL default 0,
+    last_error text NULL,
+    created_at TIMESTAMPTZ NOT NULL default NOW(),
+    next_attempt_at TIMESTAMPTZ NOT NULL default NOW(),
+    -- Set once the email ran out of attempts, it won't be retried anymore
+    dead_at TIMESTAMPTZ NULL
+);
+
+CREATE INDEX IF NOT EXISTS email_outbox_pending ON EmailOutbox (next_attempt_at) WHERE dead_at IS NULL;
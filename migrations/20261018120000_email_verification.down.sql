@@ -0,0 +1,8 @@
+ALTER TABLE Users DROP COLUMN IF EXISTS email_verified_at;
+
+DELETE FROM UserTokens WHERE purpose <> 'password_reset';
+ALTER INDEX user_tokens_username RENAME TO password_resets_username;
+ALTER TABLE UserTokens RENAME CONSTRAINT usertokens_username_fkey TO passwordresets_username_fkey;
+ALTER TABLE UserTokens RENAME CONSTRAINT usertokens_token_key TO passwordresets_token_key;
+ALTER TABLE UserTokens RENAME CONSTRAINT usertokens_pkey TO passwordresets_pkey;
+ALTER TABLE UserTokens RENAME TO PasswordResets;
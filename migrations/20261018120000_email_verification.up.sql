@@ -0,0 +1,8 @@
+-- One-time tokens are not only used for password resets anymore, the purpose column tells them apart
+ALTER TABLE PasswordResets RENAME TO UserTokens;
+ALTER TABLE UserTokens RENAME CONSTRAINT passwordresets_pkey TO usertokens_pkey;
+ALTER TABLE UserTokens RENAME CONSTRAINT passwordresets_token_key TO usertokens_token_key;
+ALTER TABLE UserTokens RENAME CONSTRAINT passwordresets_username_fkey TO usertokens_username_fkey;
+ALTER INDEX password_resets_username RENAME TO user_tokens_username;
+
+ALTER TABLE Users ADD COLUMN IF NOT EXISTS email_verified_at TIMESTAMPTZ NULL;
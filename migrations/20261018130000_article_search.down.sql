@@ -0,0 +1,3 @@
+DROP TRIGGER IF EXISTS articles_search_document ON Articles;
+DROP FUNCTION IF EXISTS articles_search_document;
+ALTER TABLE Articles DROP COLUMN IF EXISTS search;
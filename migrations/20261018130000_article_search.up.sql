@@ -0,0 +1,23 @@
+ALTER TABLE Articles ADD COLUMN IF NOT EXISTS search tsvector NOT NULL default ''::tsvector;
+
+-- Matches in the title weight more than in the description, and these more than in the body
+CREATE OR REPLACE FUNCTION articles_search_document() RETURNS trigger AS $$
+BEGIN
+    NEW.search :=
+        setweight(to_tsvector('english', NEW.title), 'A') ||
+        setweight(to_tsvector('english', NEW.description), 'B') ||
+        setweight(to_tsvector('english', NEW.body), 'C');
+    RETURN NEW;
+END
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE TRIGGER articles_search_document
+    BEFORE INSERT OR UPDATE OF title, description, body ON Articles
+    FOR EACH ROW EXECUTE FUNCTION articles_search_document();
+
+UPDATE Articles SET
+    search = setweight(to_tsvector('english', title), 'A') ||
+        setweight(to_tsvector('english', description), 'B') ||
+        setweight(to_tsvector('english', body), 'C');
+
+CREATE INDEX IF NOT EXISTS articles_search ON Articles USING GIN (search);
@@ -0,0 +1,8 @@
+-- Previous slugs of renamed articles, so links to them keep working
+CREATE TABLE IF NOT EXISTS SlugAliases (
+    slug text NOT NULL PRIMARY KEY,
+    article text NOT NULL REFERENCES Articles(slug) ON DELETE CASCADE ON UPDATE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL default NOW()
+);
+
+CREATE INDEX IF NOT EXISTS slug_aliases_article ON SlugAliases (article);
@@ -0,0 +1,83 @@
+ALTER TABLE Articles ADD COLUMN author text;
+UPDATE Articles SET author = u.username FROM Users u WHERE u.id = Articles.author_id;
+
+ALTER TABLE Follows ADD COLUMN follower text, ADD COLUMN influencer text;
+UPDATE Follows SET
+    follower = (SELECT username FROM Users WHERE id = Follows.follower_id),
+    influencer = (SELECT username FROM Users WHERE id = Follows.influencer_id);
+
+ALTER TABLE ArticleTags ADD COLUMN article text;
+UPDATE ArticleTags SET article = a.slug FROM Articles a WHERE a.id = ArticleTags.article_id;
+
+ALTER TABLE FavArticles ADD COLUMN article text, ADD COLUMN username text;
+UPDATE FavArticles SET
+    article = (SELECT slug FROM Articles WHERE id = FavArticles.article_id),
+    username = (SELECT username FROM Users WHERE id = FavArticles.user_id);
+
+ALTER TABLE Comments ADD COLUMN article text, ADD COLUMN username text;
+UPDATE Comments SET
+    article = (SELECT slug FROM Articles WHERE id = Comments.article_id),
+    username = (SELECT username FROM Users WHERE id = Comments.user_id);
+
+ALTER TABLE SlugAliases ADD COLUMN article text;
+UPDATE SlugAliases SET article = a.slug FROM Articles a WHERE a.id = SlugAliases.article_id;
+
+ALTER TABLE Sessions ADD COLUMN username text;
+UPDATE Sessions SET username = u.username FROM Users u WHERE u.id = Sessions.user_id;
+
+ALTER TABLE UserTokens ADD COLUMN username text;
+UPDATE UserTokens SET username = u.username FROM Users u WHERE u.id = UserTokens.user_id;
+
+ALTER TABLE Follows DROP COLUMN follower_id, DROP COLUMN influencer_id;
+ALTER TABLE ArticleTags DROP COLUMN article_id;
+ALTER TABLE FavArticles DROP COLUMN article_id, DROP COLUMN user_id;
+ALTER TABLE Comments DROP COLUMN article_id, DROP COLUMN user_id;
+ALTER TABLE SlugAliases DROP COLUMN article_id;
+ALTER TABLE Sessions DROP COLUMN user_id;
+ALTER TABLE UserTokens DROP COLUMN user_id;
+ALTER TABLE Articles DROP COLUMN author_id;
+
+ALTER TABLE Articles DROP CONSTRAINT articles_slug_key;
+ALTER TABLE Articles DROP CONSTRAINT articles_pkey;
+ALTER TABLE Articles ADD PRIMARY KEY (slug);
+ALTER TABLE Articles DROP COLUMN id;
+ALTER TABLE Users DROP CONSTRAINT users_username_key;
+ALTER TABLE Users DROP CONSTRAINT users_pkey;
+ALTER TABLE Users ADD PRIMARY KEY (username);
+ALTER TABLE Users DROP COLUMN id;
+
+ALTER TABLE Articles ALTER COLUMN author SET NOT NULL,
+    ADD FOREIGN KEY (author) REFERENCES Users(username) ON DELETE CASCADE ON UPDATE CASCADE;
+
+ALTER TABLE Follows ALTER COLUMN follower SET NOT NULL,
+    ALTER COLUMN influencer SET NOT NULL,
+    ADD FOREIGN KEY (follower) REFERENCES Users(username) ON DELETE CASCADE ON UPDATE CASCADE,
+    ADD FOREIGN KEY (influencer) REFERENCES Users(username) ON DELETE CASCADE ON UPDATE CASCADE,
+    ADD PRIMARY KEY (follower, influencer);
+
+ALTER TABLE ArticleTags ALTER COLUMN article SET NOT NULL,
+    ADD FOREIGN KEY (article) REFERENCES Articles(slug) ON DELETE CASCADE ON UPDATE CASCADE,
+    ADD PRIMARY KEY (article, tag);
+
+ALTER TABLE FavArticles ALTER COLUMN article SET NOT NULL,
+    ALTER COLUMN username SET NOT NULL,
+    ADD FOREIGN KEY (article) REFERENCES Articles(slug) ON DELETE CASCADE ON UPDATE CASCADE,
+    ADD FOREIGN KEY (username) REFERENCES Users(username) ON DELETE CASCADE ON UPDATE CASCADE,
+    ADD PRIMARY KEY (article, username);
+
+ALTER TABLE Comments ALTER COLUMN article SET NOT NULL,
+    ALTER COLUMN username SET NOT NULL,
+    ADD FOREIGN KEY (article) REFERENCES Articles(slug) ON DELETE CASCADE ON UPDATE CASCADE,
+    ADD FOREIGN KEY (username) REFERENCES Users(username) ON DELETE CASCADE ON UPDATE CASCADE;
+
+ALTER TABLE SlugAliases ALTER COLUMN article SET NOT NULL,
+    ADD FOREIGN KEY (article) REFERENCES Articles(slug) ON DELETE CASCADE ON UPDATE CASCADE;
+CREATE INDEX IF NOT EXISTS slug_aliases_article ON SlugAliases (article);
+
+ALTER TABLE Sessions ALTER COLUMN username SET NOT NULL,
+    ADD FOREIGN KEY (username) REFERENCES Users(username) ON DELETE CASCADE ON UPDATE CASCADE;
+CREATE INDEX IF NOT EXISTS sessions_username ON Sessions (username);
+
+ALTER TABLE UserTokens ALTER COLUMN username SET NOT NULL,
+    ADD CONSTRAINT usertokens_username_fkey FOREIGN KEY (username) REFERENCES Users(username) ON DELETE CASCADE ON UPDATE CASCADE;
+CREATE INDEX IF NOT EXISTS user_tokens_username ON UserTokens (username);
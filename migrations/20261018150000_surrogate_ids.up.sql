@@ -0,0 +1,91 @@
+-- Users and articles are identified by a surrogate key, so usernames and slugs can change
+-- without rewriting every row that points to them
+ALTER TABLE Users ADD COLUMN id bigint GENERATED ALWAYS AS IDENTITY;
+ALTER TABLE Articles ADD COLUMN id bigint GENERATED ALWAYS AS IDENTITY;
+
+ALTER TABLE Articles ADD COLUMN author_id bigint;
+UPDATE Articles SET author_id = u.id FROM Users u WHERE u.username = Articles.author;
+
+ALTER TABLE Follows ADD COLUMN follower_id bigint, ADD COLUMN influencer_id bigint;
+UPDATE Follows SET
+    follower_id = (SELECT id FROM Users WHERE username = Follows.follower),
+    influencer_id = (SELECT id FROM Users WHERE username = Follows.influencer);
+
+ALTER TABLE ArticleTags ADD COLUMN article_id bigint;
+UPDATE ArticleTags SET article_id = a.id FROM Articles a WHERE a.slug = ArticleTags.article;
+
+ALTER TABLE FavArticles ADD COLUMN article_id bigint, ADD COLUMN user_id bigint;
+UPDATE FavArticles SET
+    article_id = (SELECT id FROM Articles WHERE slug = FavArticles.article),
+    user_id = (SELECT id FROM Users WHERE username = FavArticles.username);
+
+ALTER TABLE Comments ADD COLUMN article_id bigint, ADD COLUMN user_id bigint;
+UPDATE Comments SET
+    article_id = (SELECT id FROM Articles WHERE slug = Comments.article),
+    user_id = (SELECT id FROM Users WHERE username = Comments.username);
+
+ALTER TABLE SlugAliases ADD COLUMN article_id bigint;
+UPDATE SlugAliases SET article_id = a.id FROM Articles a WHERE a.slug = SlugAliases.article;
+
+ALTER TABLE Sessions ADD COLUMN user_id bigint;
+UPDATE Sessions SET user_id = u.id FROM Users u WHERE u.username = Sessions.username;
+
+ALTER TABLE UserTokens ADD COLUMN user_id bigint;
+UPDATE UserTokens SET user_id = u.id FROM Users u WHERE u.username = UserTokens.username;
+
+-- Dropping the old columns drops their foreign keys, primary keys and indexes as well
+ALTER TABLE Follows DROP COLUMN follower, DROP COLUMN influencer;
+ALTER TABLE ArticleTags DROP COLUMN article;
+ALTER TABLE FavArticles DROP COLUMN article, DROP COLUMN username;
+ALTER TABLE Comments DROP COLUMN article, DROP COLUMN username;
+ALTER TABLE SlugAliases DROP COLUMN article;
+ALTER TABLE Sessions DROP COLUMN username;
+ALTER TABLE UserTokens DROP COLUMN username;
+ALTER TABLE Articles DROP COLUMN author;
+
+ALTER TABLE Articles DROP CONSTRAINT articles_pkey;
+ALTER TABLE Articles ADD PRIMARY KEY (id);
+ALTER TABLE Articles ADD CONSTRAINT articles_slug_key UNIQUE (slug);
+ALTER TABLE Users DROP CONSTRAINT users_pkey;
+ALTER TABLE Users ADD PRIMARY KEY (id);
+ALTER TABLE Users ADD CONSTRAINT users_username_key UNIQUE (username);
+
+ALTER TABLE Articles ALTER COLUMN author_id SET NOT NULL,
+    ADD FOREIGN KEY (author_id) REFERENCES Users(id) ON DELETE CASCADE;
+CREATE INDEX IF NOT EXISTS articles_author_id ON Articles (author_id);
+
+ALTER TABLE Follows ALTER COLUMN follower_id SET NOT NULL,
+    ALTER COLUMN influencer_id SET NOT NULL,
+    ADD FOREIGN KEY (follower_id) REFERENCES Users(id) ON DELETE CASCADE,
+    ADD FOREIGN KEY (influencer_id) REFERENCES Users(id) ON DELETE CASCADE,
+    ADD PRIMARY KEY (follower_id, influencer_id);
+CREATE INDEX IF NOT EXISTS follows_influencer_id ON Follows (influencer_id);
+
+ALTER TABLE ArticleTags ALTER COLUMN article_id SET NOT NULL,
+    ADD FOREIGN KEY (article_id) REFERENCES Articles(id) ON DELETE CASCADE,
+    ADD PRIMARY KEY (article_id, tag);
+
+ALTER TABLE FavArticles ALTER COLUMN article_id SET NOT NULL,
+    ALTER COLUMN user_id SET NOT NULL,
+    ADD FOREIGN KEY (article_id) REFERENCES Articles(id) ON DELETE CASCADE,
+    ADD FOREIGN KEY (user_id) REFERENCES Users(id) ON DELETE CASCADE,
+    ADD PRIMARY KEY (article_id, user_id);
+CREATE INDEX IF NOT EXISTS fav_articles_user_id ON FavArticles (user_id);
+
+ALTER TABLE Comments ALTER COLUMN article_id SET NOT NULL,
+    ALTER COLUMN user_id SET NOT NULL,
+    ADD FOREIGN KEY (article_id) REFERENCES Articles(id) ON DELETE CASCADE,
+    ADD FOREIGN KEY (user_id) REFERENCES Users(id) ON DELETE CASCADE;
+CREATE INDEX IF NOT EXISTS comments_article_id ON Comments (article_id);
+
+ALTER TABLE SlugAliases ALTER COLUMN article_id SET NOT NULL,
+    ADD FOREIGN KEY (article_id) REFERENCES Articles(id) ON DELETE CASCADE;
+CREATE INDEX IF NOT EXISTS slug_aliases_article_id ON SlugAliases (article_id);
+
+ALTER TABLE Sessions ALTER COLUMN user_id SET NOT NULL,
+    ADD FOREIGN KEY (user_id) REFERENCES Users(id) ON DELETE CASCADE;
+CREATE INDEX IF NOT EXISTS sessions_user_id ON Sessions (user_id);
+
+ALTER TABLE UserTokens ALTER COLUMN user_id SET NOT NULL,
+    ADD FOREIGN KEY (user_id) REFERENCES Users(id) ON DELETE CASCADE;
+CREATE INDEX IF NOT EXISTS user_tokens_user_id ON UserTokens (user_id);
@@ -0,0 +1,9 @@
+-- Previous usernames of renamed users, so links to their profiles keep working.
+-- They also tell when the user was renamed for the last time.
+CREATE TABLE IF NOT EXISTS UsernameAliases (
+    username text NOT NULL PRIMARY KEY,
+    user_id bigint NOT NULL REFERENCES Users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL default NOW()
+);
+
+CREATE INDEX IF NOT EXISTS username_aliases_user_id ON UsernameAliases (user_id);
@@ -0,0 +1,2 @@
+DELETE FROM Comments WHERE user_id IS NULL;
+ALTER TABLE Comments ALTER COLUMN user_id SET NOT NULL;
@@ -0,0 +1,2 @@
+-- Users deleting their account can leave their comments behind without their name
+ALTER TABLE Comments ALTER COLUMN user_id DROP NOT NULL;
@@ -0,0 +1,7 @@
+DROP INDEX IF EXISTS articles_scheduled;
+DROP INDEX IF EXISTS articles_published;
+-- The unpublished articles would become public otherwise
+DELETE FROM Articles WHERE status != 'published';
+ALTER TABLE Articles DROP CONSTRAINT articles_published_at_check;
+ALTER TABLE Articles DROP COLUMN published_at;
+ALTER TABLE Articles DROP COLUMN status;
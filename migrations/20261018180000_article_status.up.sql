@@ -0,0 +1,12 @@
+-- Existing articles were published when they were created
+ALTER TABLE Articles ADD COLUMN status text NOT NULL default 'published'
+    CHECK (status IN ('draft', 'published', 'scheduled'));
+ALTER TABLE Articles ADD COLUMN published_at TIMESTAMPTZ NULL;
+UPDATE Articles SET published_at = created_at;
+ALTER TABLE Articles ALTER COLUMN status SET default 'draft';
+-- Drafts don't have a date yet, scheduled articles are published once it comes
+ALTER TABLE Articles ADD CONSTRAINT articles_published_at_check
+    CHECK ((status = 'draft') = (published_at IS NULL));
+
+CREATE INDEX IF NOT EXISTS articles_published ON Articles (published_at, slug) WHERE status = 'published';
+CREATE INDEX IF NOT EXISTS articles_scheduled ON Articles (published_at) WHERE status = 'scheduled';
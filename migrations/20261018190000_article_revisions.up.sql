@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS ArticleRevisions (
+    id bigint PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+    article_id bigint NOT NULL REFERENCES Articles(id) ON DELETE CASCADE,
+    title text NOT NULL,
+    description text NOT NULL,
+    body text NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL default NOW()
+);
+
+CREATE INDEX IF NOT EXISTS article_revisions_article_id ON ArticleRevisions (article_id);
+
+-- The history of the existing articles starts with their current content
+INSERT INTO ArticleRevisions(article_id, title, description, body, created_at)
+SELECT id, title, description, body, updated_at FROM Articles;
@@ -0,0 +1,2 @@
+-- Bumped on every update of the article, edits made from an older one are rejected
+ALTER TABLE Articles ADD COLUMN version bigint NOT NULL default 0;
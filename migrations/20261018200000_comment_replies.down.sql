@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS comments_parent_id;
+-- The placeholders stay as comments without author, so their replies aren't lost
+ALTER TABLE Comments DROP COLUMN deleted_at;
+ALTER TABLE Comments DROP COLUMN parent_id;
@@ -0,0 +1,5 @@
+ALTER TABLE Comments ADD COLUMN parent_id integer NULL REFERENCES Comments(id) ON DELETE CASCADE;
+-- Deleted comments with replies stay as placeholders, without author nor body
+ALTER TABLE Comments ADD COLUMN deleted_at TIMESTAMPTZ NULL;
+
+CREATE INDEX IF NOT EXISTS comments_parent_id ON Comments (parent_id);
@@ -0,0 +1 @@
+ALTER TABLE Comments ADD COLUMN edited_at TIMESTAMPTZ NULL;
@@ -0,0 +1,2 @@
+-- The comments are paginated by thread, the ones without parent
+CREATE INDEX IF NOT EXISTS comments_threads ON Comments (article_id, created_at, id) WHERE parent_id IS NULL;
@@ -0,0 +1,3 @@
+DROP TRIGGER IF EXISTS fav_articles_live ON FavArticles;
+DROP TRIGGER IF EXISTS comments_live ON Comments;
+DROP FUNCTION IF EXISTS notify_live;
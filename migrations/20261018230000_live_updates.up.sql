@@ -0,0 +1,36 @@
+-- Readers of an article get its new counts as they change, the server forwards the
+-- notifications of the `live` channel to them. Equal payloads of one transaction go as one.
+CREATE OR REPLACE FUNCTION notify_live() RETURNS trigger AS $$
+DECLARE
+    changed_article bigint;
+    changed_slug text;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        changed_article := OLD.article_id;
+    ELSE
+        changed_article := NEW.article_id;
+    END IF;
+    SELECT slug INTO changed_slug FROM Articles WHERE id = changed_article;
+    -- Deleted along with its article, nobody is reading it anymore
+    IF changed_slug IS NULL THEN
+        RETURN NULL;
+    END IF;
+    PERFORM pg_notify('live', json_build_object(
+        'kind', TG_ARGV[0],
+        'article', changed_slug,
+        'count', CASE TG_ARGV[0]
+            WHEN 'comments' THEN (SELECT COUNT(*) FROM Comments WHERE article_id = changed_article AND deleted_at IS NULL)
+            ELSE (SELECT COUNT(*) FROM FavArticles WHERE article_id = changed_article)
+        END
+    )::text);
+    RETURN NULL;
+END
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE TRIGGER comments_live
+    AFTER INSERT OR UPDATE OR DELETE ON Comments
+    FOR EACH ROW EXECUTE FUNCTION notify_live('comments');
+
+CREATE OR REPLACE TRIGGER fav_articles_live
+    AFTER INSERT OR DELETE ON FavArticles
+    FOR EACH ROW EXECUTE FUNCTION notify_live('favorites');
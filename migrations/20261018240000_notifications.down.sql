@@ -0,0 +1,4 @@
+ALTER TABLE Users DROP COLUMN notify_comments;
+ALTER TABLE Users DROP COLUMN notify_favorites;
+ALTER TABLE Users DROP COLUMN notify_follows;
+DROP TABLE IF EXISTS Notifications;
@@ -0,0 +1,20 @@
+CREATE TABLE IF NOT EXISTS Notifications (
+    id bigint PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+    -- The user notified
+    user_id bigint NOT NULL REFERENCES Users(id) ON DELETE CASCADE,
+    -- The user who followed, favorited or commented
+    actor_id bigint NOT NULL REFERENCES Users(id) ON DELETE CASCADE,
+    kind text NOT NULL CHECK (kind IN ('follow', 'favorite', 'comment')),
+    article_id bigint NULL REFERENCES Articles(id) ON DELETE CASCADE,
+    comment_id int NULL REFERENCES Comments(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL default NOW(),
+    read_at TIMESTAMPTZ NULL
+);
+
+CREATE INDEX IF NOT EXISTS notifications_user_id ON Notifications (user_id, id);
+CREATE INDEX IF NOT EXISTS notifications_unread ON Notifications (user_id) WHERE read_at IS NULL;
+
+-- Every kind of notification can be turned off
+ALTER TABLE Users ADD COLUMN notify_follows boolean NOT NULL default true;
+ALTER TABLE Users ADD COLUMN notify_favorites boolean NOT NULL default true;
+ALTER TABLE Users ADD COLUMN notify_comments boolean NOT NULL default true;
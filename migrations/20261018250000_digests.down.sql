@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS users_digest;
+ALTER TABLE Users DROP COLUMN digest_sent_at;
+ALTER TABLE Users DROP COLUMN digest_frequency;
@@ -0,0 +1,6 @@
+ALTER TABLE Users ADD COLUMN digest_frequency text NOT NULL default 'never'
+    CHECK (digest_frequency in ('never', 'daily', 'weekly'));
+-- Articles published after it go in the next digest
+ALTER TABLE Users ADD COLUMN digest_sent_at TIMESTAMPTZ NULL;
+
+CREATE INDEX IF NOT EXISTS users_digest ON Users(digest_sent_at) WHERE digest_frequency != 'never';
@@ -0,0 +1,3 @@
+-- When the article became public, scheduled articles only do when the scheduler publishes them
+ALTER TABLE Articles ADD COLUMN live_at TIMESTAMPTZ NULL;
+UPDATE Articles SET live_at=published_at WHERE status='published';
@@ -1,10 +1,13 @@
 use leptos::prelude::*;
-use leptos_meta::{MetaTags, Stylesheet, provide_meta_context};
+use leptos_meta::{Link, MetaTags, Stylesheet, provide_meta_context};
 use leptos_router::components::{A, Route, Router, Routes};
-use leptos_router::path;
+use leptos_router::{SsrMode, path};
 
 use crate::components::NavItems;
-use crate::routes::{Article, Editor, HomePage, Login, Profile, ResetPassword, Settings, Signup};
+use crate::routes::{
+    Article, ArticleHistory, Editor, HomePage, Login, Notifications, Profile, ResetPassword,
+    Search, Settings, Signup, Unsubscribe, VerifyEmail,
+};
 
 pub fn shell(options: LeptosOptions) -> impl IntoView {
     view! {
@@ -34,18 +37,41 @@ pub fn App() -> impl IntoView {
     let username: crate::auth::UsernameSignal = RwSignal::new(None);
 
     let logout: crate::auth::LogoutSignal = ServerAction::<crate::auth::LogoutAction>::new();
+    let logout_all: crate::auth::LogoutAllSignal =
+        ServerAction::<crate::auth::LogoutAllAction>::new();
     let login: crate::auth::LoginSignal = ServerAction::<crate::auth::LoginAction>::new();
     let signup: crate::auth::SignupSignal = ServerAction::<crate::auth::SignupAction>::new();
+    // Renaming the user changes the links of the navigation bar
+    let settings = ServerAction::<crate::routes::SettingsUpdateAction>::new();
+    let delete_account = ServerAction::<crate::routes::DeleteAccountAction>::new();
+    // Reading the notifications updates the unread count of the navigation bar
+    let mark_read = ServerAction::<crate::routes::MarkNotificationsReadAction>::new();
 
-    let (logout_version, login_version, signup_version) =
-        (logout.version(), login.version(), signup.version());
+    let (
+        logout_version,
+        logout_all_version,
+        login_version,
+        signup_version,
+        settings_version,
+        delete_account_version,
+    ) = (
+        logout.version(),
+        logout_all.version(),
+        login.version(),
+        signup.version(),
+        settings.version(),
+        delete_account.version(),
+    );
 
     let user = Resource::new(
         move || {
             (
                 logout_version.get(),
+                logout_all_version.get(),
                 login_version.get(),
                 signup_version.get(),
+                settings_version.get(),
+                delete_account_version.get(),
             )
         },
         move |_| {
@@ -70,6 +96,7 @@ pub fn App() -> impl IntoView {
         <Stylesheet href="https://fonts.googleapis.com/css?family=Titillium+Web:700|Source+Serif+Pro:400,700|Merriweather+Sans:400,700|Source+Sans+Pro:400,300,600,700,300italic,400italic,600italic,700italic"/>
         <Stylesheet href="https://demo.productionready.io/main.css"/>
         <Stylesheet href="/pkg/realworld-leptos.css"/>
+        <Link rel="alternate" type_="application/atom+xml" title="conduit" href="/feed.xml"/>
 
         <Router>
             <nav class="navbar navbar-light">
@@ -79,7 +106,7 @@ pub fn App() -> impl IntoView {
                         <Transition fallback=|| view!{<p>"Loading Navigation bar"</p>}>
                         {move || user.get().map(move |_| {
                             view! {
-                                <NavItems logout username />
+                                <NavItems logout username mark_read />
                             }
                         })}
                         </Transition>
@@ -100,9 +127,13 @@ pub fn App() -> impl IntoView {
                     <Route path=path!("/login") view=move || view! { <Login login/> }/>
                     <Route path=path!("/reset_password") view=move || view! { <ResetPassword/> }/>
                     <Route path=path!("/signup") view=move || view! { <Signup signup/> }/>
-                    <Route path=path!("/settings") view=move || view! { <Settings logout /> }/>
+                    <Route path=path!("/verify_email") view=|| view! { <VerifyEmail/> }/>
+                    <Route path=path!("/unsubscribe") view=|| view! { <Unsubscribe/> }/>
+                    <Route path=path!("/settings") view=move || view! { <Settings logout logout_all settings delete_account /> }/>
+                    <Route path=path!("/notifications") view=move || view! { <Notifications mark_read /> }/>
                     <Route path=path!("/editor/:slug?") view=|| view! { <Editor/> }/>
-                    <Route path=path!("/article/:slug") view=move || view! {
+                    // Rendered at once, so renamed articles can answer with a permanent redirect
+                    <Route path=path!("/article/:slug") ssr=SsrMode::Async view=move || view! {
                         <Transition fallback=|| view!{<p>"Loading Article"</p>}>
                         {move || user.get().map(move |_| {
                             view! {
@@ -111,7 +142,18 @@ pub fn App() -> impl IntoView {
                         })}
                         </Transition>
                     }/>
-                    <Route path=path!("/profile/:user") view=move || view! {
+                    <Route path=path!("/article/:slug/history") view=|| view! { <ArticleHistory/> }/>
+                    <Route path=path!("/search") view=move || view! {
+                        <Transition fallback=|| view!{<p>"Loading Search"</p>}>
+                        {move || user.get().map(move |_| {
+                            view! {
+                                <Search username/>
+                            }
+                        })}
+                        </Transition>
+                    }/>
+                    // Rendered at once, so renamed users can answer with a permanent redirect
+                    <Route path=path!("/profile/:user") ssr=SsrMode::Async view=move || view! {
                         <Transition fallback=|| view!{<p>"Loading Profile"</p>}>
                         {move || user.get().map(move |_| {
                             view! {
@@ -19,6 +19,18 @@ pub fn validate_signup(
         .set_email(email)
 }
 
+/// Inserts the user and queues the email to verify its address.
+#[cfg(feature = "ssr")]
+async fn register(user: &crate::models::User) -> Result<(), ServerFnError> {
+    let host = leptos_axum::extract::<axum_extra::extract::Host>().await?.0;
+    let mut transaction = crate::database::get_db().begin().await?;
+    user.insert(transaction.as_mut()).await?;
+    super::EmailVerification::send(transaction.as_mut(), user.username(), user.email(), &host)
+        .await?;
+    transaction.commit().await?;
+    Ok(())
+}
+
 #[tracing::instrument]
 #[server(SignupAction, "/api")]
 pub async fn signup_action(
@@ -27,8 +39,9 @@ pub async fn signup_action(
     password: String,
 ) -> Result<SignupResponse, ServerFnError> {
     match validate_signup(username.clone(), email, password) {
-        Ok(user) => match user.insert().await {
-            Ok(_) => {
+        Ok(user) => match register(&user).await {
+            Ok(()) => {
+                crate::mailer::wake_worker();
                 crate::auth::set_username(username).await;
                 leptos_axum::redirect("/");
                 Ok(SignupResponse::Success)
@@ -37,7 +50,7 @@ pub async fn signup_action(
                 let x = x.to_string();
                 Ok(if x.contains("users_email_key") {
                     SignupResponse::CreateUserError("Duplicated email".to_string())
-                } else if x.contains("users_pkey") {
+                } else if x.contains("users_username_key") {
                     SignupResponse::CreateUserError("Duplicated user".to_string())
                 } else {
                     tracing::error!("error from DB: {}", x);
@@ -86,12 +99,29 @@ pub async fn login_action(
 #[server(LogoutAction, "/api")]
 #[tracing::instrument]
 pub async fn logout_action() -> Result<(), ServerFnError> {
-    let response_options = use_context::<leptos_axum::ResponseOptions>().unwrap();
-    response_options.insert_header(
-        axum::http::header::SET_COOKIE,
-        axum::http::HeaderValue::from_str(crate::auth::REMOVE_COOKIE)
-            .expect("header value couldn't be set"),
-    );
+    if let Some(sid) = super::get_session_id()
+        && let Err(x) = super::Session::revoke(sid).await
+    {
+        tracing::error!("problem while revoking the session {sid}: {x:?}");
+    }
+    super::remove_username();
+    leptos_axum::redirect("/login");
+    Ok(())
+}
+
+#[server(LogoutAllAction, "/api")]
+#[tracing::instrument]
+pub async fn logout_all_action() -> Result<(), ServerFnError> {
+    let Some(username) = super::get_username() else {
+        return Err(ServerFnError::ServerError("you must be logged in".into()));
+    };
+    super::Session::revoke_all(crate::database::get_db(), username)
+        .await
+        .map_err(|x| {
+            tracing::error!("problem while revoking the sessions: {x:?}");
+            ServerFnError::ServerError("Could not log out from all devices, try again later".into())
+        })?;
+    super::remove_username();
     leptos_axum::redirect("/login");
     Ok(())
 }
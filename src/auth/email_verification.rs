@@ -0,0 +1,68 @@
+const PURPOSE: &str = "email_verification";
+const EXPIRATION_HOURS: i32 = 48;
+
+/// Proves the user owns the email they registered with, until then they can't publish.
+pub(crate) struct EmailVerification;
+
+impl EmailVerification {
+    /// Issues a token for the user's current email and queues the email carrying it,
+    /// invalidating the ones sent before.
+    ///
+    /// It runs inside the caller's transaction, remember to wake the mailer after committing.
+    #[tracing::instrument(skip(connection))]
+    pub async fn send(
+        connection: &mut sqlx::PgConnection,
+        username: String,
+        email: String,
+        host: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "DELETE FROM UserTokens WHERE user_id=(SELECT id FROM Users WHERE username=$1) and purpose=$2",
+            username,
+            PURPOSE,
+        )
+        .execute(&mut *connection)
+        .await?;
+        let token = sqlx::query_scalar!(
+            "
+WITH new_token AS (SELECT encode(gen_random_bytes(32), 'hex') as value)
+INSERT INTO UserTokens(user_id, token, purpose, expires_at)
+SELECT u.id, encode(digest(new_token.value, 'sha256'), 'hex'), $2, NOW() + make_interval(hours => $3)
+FROM new_token, Users as u
+WHERE u.username=$1
+RETURNING (SELECT value FROM new_token) as token",
+            username,
+            PURPOSE,
+            EXPIRATION_HOURS,
+        )
+        .fetch_one(&mut *connection)
+        .await?
+        .unwrap_or_default();
+        let link = crate::mailer::site_link(host, &format!("/verify_email?token={token}"));
+        crate::mailer::Email::email_verification(email, &username, &link)
+            .enqueue(connection)
+            .await?;
+        Ok(())
+    }
+
+    /// Consumes the token and marks the email as verified.
+    ///
+    /// Returns the affected user, or `None` if the token is unknown, used or expired.
+    #[tracing::instrument(skip_all)]
+    pub async fn verify(token: String) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar!(
+            "
+WITH used AS (
+    UPDATE UserTokens SET used_at=NOW()
+    WHERE token=encode(digest($1, 'sha256'), 'hex') and purpose=$2 and used_at IS NULL and expires_at > NOW()
+    RETURNING user_id
+)
+UPDATE Users SET email_verified_at=NOW() FROM used WHERE Users.id=used.user_id
+RETURNING Users.username",
+            token,
+            PURPOSE,
+        )
+        .fetch_optional(crate::database::get_db())
+        .await
+    }
+}
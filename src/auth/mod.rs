@@ -1,12 +1,29 @@
 use leptos::prelude::*;
 mod api;
 #[cfg(feature = "ssr")]
+mod email_verification;
+#[cfg(feature = "ssr")]
+mod password_reset;
+#[cfg(feature = "ssr")]
 mod server;
+#[cfg(feature = "ssr")]
+mod session;
+#[cfg(feature = "ssr")]
+mod unsubscribe;
 pub use api::*;
 #[cfg(feature = "ssr")]
+pub(crate) use email_verification::EmailVerification;
+#[cfg(feature = "ssr")]
+pub(crate) use password_reset::PasswordReset;
+#[cfg(feature = "ssr")]
 pub use server::*;
+#[cfg(feature = "ssr")]
+pub(crate) use session::Session;
+#[cfg(feature = "ssr")]
+pub(crate) use unsubscribe::DigestUnsubscribe;
 
 pub type LogoutSignal = ServerAction<LogoutAction>;
+pub type LogoutAllSignal = ServerAction<LogoutAllAction>;
 pub type LoginSignal = ServerAction<LoginAction>;
 pub type SignupSignal = ServerAction<SignupAction>;
 pub type UsernameSignal = RwSignal<Option<String>>;
@@ -0,0 +1,86 @@
+/// Tokens are tied to what they were issued for, a reset token can't be used for anything else.
+const PURPOSE: &str = "password_reset";
+const EXPIRATION_MINUTES: i32 = 60;
+
+/// One-time token mailed to the user to choose a new password.
+#[derive(Debug)]
+pub(crate) struct PasswordReset {
+    pub username: String,
+    /// Plain token, only known right after issuing it.
+    pub token: String,
+}
+
+impl PasswordReset {
+    /// Issues a new token for the user, invalidating the ones requested before.
+    ///
+    /// It runs inside the caller's transaction, so the token only exists if the email
+    /// carrying it gets queued too.
+    #[tracing::instrument(skip(connection))]
+    pub async fn create(
+        connection: &mut sqlx::PgConnection,
+        username: String,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query!(
+            "DELETE FROM UserTokens WHERE user_id=(SELECT id FROM Users WHERE username=$1) and purpose=$2",
+            username,
+            PURPOSE,
+        )
+        .execute(&mut *connection)
+        .await?;
+        let reset = sqlx::query!(
+            "
+WITH new_token AS (SELECT encode(gen_random_bytes(32), 'hex') as value)
+INSERT INTO UserTokens(user_id, token, purpose, expires_at)
+SELECT u.id, encode(digest(new_token.value, 'sha256'), 'hex'), $2, NOW() + make_interval(mins => $3)
+FROM new_token, Users as u
+WHERE u.username=$1
+RETURNING
+    (SELECT username FROM Users WHERE Users.id=UserTokens.user_id) as \"username!\",
+    (SELECT value FROM new_token) as token",
+            username,
+            PURPOSE,
+            EXPIRATION_MINUTES,
+        )
+        .map(|x| Self {
+            username: x.username,
+            token: x.token.unwrap_or_default(),
+        })
+        .fetch_one(connection)
+        .await?;
+        Ok(reset)
+    }
+
+    /// Consumes the token and sets the new password, logging the user out everywhere.
+    ///
+    /// Returns the affected user, or `None` if the token is unknown, used or expired.
+    #[tracing::instrument(skip_all)]
+    pub async fn reset_password(
+        token: String,
+        password: String,
+    ) -> Result<Option<String>, sqlx::Error> {
+        let mut transaction = crate::database::get_db().begin().await?;
+        let Some(username) = sqlx::query_scalar!(
+            "
+UPDATE UserTokens SET used_at=NOW()
+WHERE token=encode(digest($1, 'sha256'), 'hex') and purpose=$2 and used_at IS NULL and expires_at > NOW()
+RETURNING (SELECT username FROM Users WHERE Users.id=UserTokens.user_id) as \"username!\"",
+            token,
+            PURPOSE,
+        )
+        .fetch_optional(transaction.as_mut())
+        .await?
+        else {
+            return Ok(None);
+        };
+        sqlx::query!(
+            "UPDATE Users SET password=crypt($2, gen_salt('bf')) WHERE username=$1",
+            username,
+            password,
+        )
+        .execute(transaction.as_mut())
+        .await?;
+        super::Session::revoke_all(transaction.as_mut(), username.clone()).await?;
+        transaction.commit().await?;
+        Ok(Some(username))
+    }
+}
@@ -1,16 +1,23 @@
 use axum::{
-    http::{Request, StatusCode, header},
+    http::{HeaderMap, HeaderValue, Request, StatusCode, header},
     response::Response,
 };
 use jsonwebtoken::{DecodingKey, Validation, decode};
 use serde::{Deserialize, Serialize};
 
+use super::Session;
+
 static AUTH_COOKIE: &str = "token";
+static REFRESH_COOKIE: &str = "refresh_token";
+/// Access tokens are short-lived, the refresh token cookie keeps the session alive.
+const ACCESS_TOKEN_SECONDS: usize = 15 * 60;
+pub(crate) const SESSION_SECONDS: usize = super::session::SESSION_DAYS as usize * 24 * 3600;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TokenClaims {
     pub sub: String, // Optional. Subject (whom token refers to)
     pub exp: usize, // Required (validate_exp defaults to true in validation). Expiration time (as UTC timestamp)
+    pub sid: i64,   // Session that issued the token, checked on every request
                     // aud: String,         // Optional. Audience
                     // iat: usize,          // Optional. Issued at (as UTC timestamp)
                     // iss: String,         // Optional. Issuer
@@ -19,31 +26,119 @@ pub struct TokenClaims {
 }
 
 pub(crate) static REMOVE_COOKIE: &str = "token=; path=/; expires=Thu, 01 Jan 1970 00:00:00 GMT";
+pub(crate) static REMOVE_REFRESH_COOKIE: &str =
+    "refresh_token=; path=/; expires=Thu, 01 Jan 1970 00:00:00 GMT";
+
+enum Authentication {
+    Anonymous,
+    /// The session could not be checked, the request goes on as anonymous
+    Unverified,
+    /// Expired or revoked session, the cookies must be removed
+    Invalid,
+    Valid,
+    Refreshed {
+        access_token: String,
+        refresh_token: Option<String>,
+    },
+}
+
+async fn authenticate(headers: &HeaderMap) -> Authentication {
+    let access_token = get_cookie(headers, AUTH_COOKIE);
+    if let Some(claims) = access_token.and_then(|x| decode_token(x).ok().map(|jwt| jwt.claims)) {
+        let sid = claims.sid;
+        return match Session::is_active(sid, &claims.sub).await {
+            Ok(true) => Authentication::Valid,
+            Ok(false) => {
+                tracing::info!("session {sid} is revoked or expired");
+                Authentication::Invalid
+            }
+            Err(x) => {
+                tracing::error!("problem while checking the session: {x:?}");
+                Authentication::Unverified
+            }
+        };
+    }
+
+    let Some(refresh_token) = get_cookie(headers, REFRESH_COOKIE) else {
+        return if access_token.is_some() {
+            Authentication::Invalid
+        } else {
+            Authentication::Anonymous
+        };
+    };
+    match Session::rotate(refresh_token).await {
+        Ok(Some(session)) => Authentication::Refreshed {
+            access_token: new_token(session.username, session.id, ACCESS_TOKEN_SECONDS),
+            refresh_token: session.refresh_token,
+        },
+        Ok(None) => {
+            tracing::info!("refresh token is not valid anymore");
+            Authentication::Invalid
+        }
+        Err(x) => {
+            tracing::error!("problem while refreshing the session: {x:?}");
+            Authentication::Unverified
+        }
+    }
+}
 
 pub async fn auth_middleware(
-    req: Request<axum::body::Body>,
+    mut req: Request<axum::body::Body>,
     next: axum::middleware::Next,
 ) -> Response {
-    match get_username_from_headers(req.headers()) {
-        Some(username) => {
-            let Ok(_) = crate::models::User::get(username).await else {
-                tracing::info!("no user associated with this token");
-                return redirect(req, next).await;
-            };
-
-            let path = req.uri().path();
-            if path.starts_with("/login") || path.starts_with("/signup") {
-                // If the user is authenticated, we don't want to show the login or signup pages
-                return Response::builder()
-                    .status(StatusCode::FOUND)
-                    .header(header::LOCATION, "/")
-                    .body(axum::body::Body::empty())
-                    .unwrap();
+    let mut set_cookies = vec![];
+    match authenticate(req.headers()).await {
+        Authentication::Anonymous => return redirect(req, next).await,
+        Authentication::Unverified => {
+            // The server functions must not trust a token we could not check
+            req.headers_mut().remove(header::COOKIE);
+            return redirect(req, next).await;
+        }
+        Authentication::Invalid => {
+            req.headers_mut().remove(header::COOKIE);
+            let mut res = redirect(req, next).await;
+            for cookie in [REMOVE_COOKIE, REMOVE_REFRESH_COOKIE] {
+                res.headers_mut()
+                    .append(header::SET_COOKIE, HeaderValue::from_static(cookie));
+            }
+            return res;
+        }
+        Authentication::Valid => {}
+        Authentication::Refreshed {
+            access_token,
+            refresh_token,
+        } => {
+            // The server functions handling this very request must see the new access token
+            req.headers_mut().insert(
+                header::COOKIE,
+                HeaderValue::from_str(&format!("{AUTH_COOKIE}={access_token}"))
+                    .expect("header value couldn't be set"),
+            );
+            set_cookies.push(access_cookie(&access_token));
+            if let Some(refresh_token) = refresh_token {
+                set_cookies.push(refresh_cookie(&refresh_token));
             }
-            next.run(req).await
         }
-        None => redirect(req, next).await,
     }
+
+    let path = req.uri().path();
+    let mut res = if path.starts_with("/login") || path.starts_with("/signup") {
+        // If the user is authenticated, we don't want to show the login or signup pages
+        Response::builder()
+            .status(StatusCode::FOUND)
+            .header(header::LOCATION, "/")
+            .body(axum::body::Body::empty())
+            .unwrap()
+    } else {
+        next.run(req).await
+    };
+    for cookie in set_cookies {
+        res.headers_mut().append(
+            header::SET_COOKIE,
+            HeaderValue::from_str(&cookie).expect("header value couldn't be set"),
+        );
+    }
+    res
 }
 
 async fn redirect(req: Request<axum::body::Body>, next: axum::middleware::Next) -> Response {
@@ -62,10 +157,15 @@ async fn redirect(req: Request<axum::body::Body>, next: axum::middleware::Next)
     }
 }
 
+/// Key signing every token issued by the server.
+pub(crate) fn jwt_secret() -> String {
+    std::env::var("JWT_SECRET").unwrap_or("replaceme when ran in prod".to_owned())
+}
+
 pub(crate) fn decode_token(
     token: &str,
 ) -> Result<jsonwebtoken::TokenData<TokenClaims>, jsonwebtoken::errors::Error> {
-    let secret = std::env::var("JWT_SECRET").unwrap_or("replaceme when ran in prod".to_owned());
+    let secret = jwt_secret();
     decode::<TokenClaims>(
         token,
         &DecodingKey::from_secret(secret.as_bytes()),
@@ -74,7 +174,7 @@ pub(crate) fn decode_token(
 }
 
 pub(crate) fn encode_token(token_claims: &TokenClaims) -> jsonwebtoken::errors::Result<String> {
-    let secret = std::env::var("JWT_SECRET").unwrap_or("replaceme when ran in prod".to_owned());
+    let secret = jwt_secret();
     jsonwebtoken::encode(
         &jsonwebtoken::Header::default(),
         &token_claims,
@@ -82,16 +182,41 @@ pub(crate) fn encode_token(token_claims: &TokenClaims) -> jsonwebtoken::errors::
     )
 }
 
-#[tracing::instrument]
-pub(crate) fn get_username_from_headers(headers: &axum::http::HeaderMap) -> Option<String> {
-    headers.get(header::COOKIE).and_then(|x| {
-        x.to_str()
-            .ok()?
-            .split("; ")
-            .find(|&x| x.starts_with(AUTH_COOKIE))
-            .and_then(|x| x.split('=').next_back())
-            .and_then(|x| decode_token(x).map(|jwt| jwt.claims.sub).ok())
+/// Mints an access token for the given user and session, shared by the cookie auth and the
+/// REST API.
+pub(crate) fn new_token(username: String, sid: i64, duration: usize) -> String {
+    encode_token(&TokenClaims {
+        sub: username,
+        exp: (sqlx::types::chrono::Utc::now().timestamp() as usize) + duration,
+        sid,
     })
+    .unwrap()
+}
+
+fn access_cookie(token: &str) -> String {
+    format!("{AUTH_COOKIE}={token}; path=/; HttpOnly")
+}
+
+fn refresh_cookie(token: &str) -> String {
+    format!("{REFRESH_COOKIE}={token}; path=/; HttpOnly; SameSite=Lax; Max-Age={SESSION_SECONDS}")
+}
+
+fn get_cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get(header::COOKIE)?
+        .to_str()
+        .ok()?
+        .split("; ")
+        .find_map(|x| x.strip_prefix(name)?.strip_prefix('='))
+}
+
+fn get_claims_from_headers(headers: &HeaderMap) -> Option<TokenClaims> {
+    get_cookie(headers, AUTH_COOKIE).and_then(|x| decode_token(x).map(|jwt| jwt.claims).ok())
+}
+
+#[tracing::instrument]
+pub(crate) fn get_username_from_headers(headers: &HeaderMap) -> Option<String> {
+    get_claims_from_headers(headers).map(|claims| claims.sub)
 }
 
 #[tracing::instrument]
@@ -100,21 +225,44 @@ pub fn get_username() -> Option<String> {
         .and_then(|req| get_username_from_headers(&req.headers))
 }
 
+#[tracing::instrument]
+pub fn get_session_id() -> Option<i64> {
+    leptos::prelude::use_context::<axum::http::request::Parts>()
+        .and_then(|req| get_claims_from_headers(&req.headers))
+        .map(|claims| claims.sid)
+}
+
+/// Opens a new session for the user and hands its tokens to the browser.
 #[tracing::instrument]
 pub async fn set_username(username: String) -> bool {
-    if let Some(res) = leptos::prelude::use_context::<leptos_axum::ResponseOptions>() {
-        let token = encode_token(&TokenClaims {
-            sub: username,
-            exp: (sqlx::types::chrono::Utc::now().timestamp() as usize) + 3_600_000,
-        })
-        .unwrap();
-        res.insert_header(
+    let Some(res) = leptos::prelude::use_context::<leptos_axum::ResponseOptions>() else {
+        return false;
+    };
+    let session = match Session::create(username).await {
+        Ok(x) => x,
+        Err(x) => {
+            tracing::error!("problem while creating the session: {x:?}");
+            return false;
+        }
+    };
+    let token = new_token(session.username, session.id, ACCESS_TOKEN_SECONDS);
+    for cookie in [
+        access_cookie(&token),
+        refresh_cookie(&session.refresh_token.unwrap_or_default()),
+    ] {
+        res.append_header(
             header::SET_COOKIE,
-            header::HeaderValue::from_str(&format!("{AUTH_COOKIE}={token}; path=/; HttpOnly"))
-                .expect("header value couldn't be set"),
+            HeaderValue::from_str(&cookie).expect("header value couldn't be set"),
         );
-        true
-    } else {
-        false
+    }
+    true
+}
+
+#[tracing::instrument]
+pub fn remove_username() {
+    if let Some(res) = leptos::prelude::use_context::<leptos_axum::ResponseOptions>() {
+        for cookie in [REMOVE_COOKIE, REMOVE_REFRESH_COOKIE] {
+            res.append_header(header::SET_COOKIE, HeaderValue::from_static(cookie));
+        }
     }
 }
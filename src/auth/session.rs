@@ -0,0 +1,141 @@
+/// How long a session lives without being used, every refresh extends it.
+pub(crate) const SESSION_DAYS: i32 = 30;
+/// Window in which the previous refresh token is still honoured, so parallel requests
+/// racing the rotation are not mistaken for a stolen token.
+const ROTATION_GRACE_SECONDS: f64 = 30.0;
+
+/// Server side record of a login, identified in the access token by its `sid` claim.
+#[derive(Debug)]
+pub(crate) struct Session {
+    pub id: i64,
+    pub username: String,
+    /// Plain refresh token, only known right after creating or rotating the session.
+    pub refresh_token: Option<String>,
+}
+
+impl Session {
+    #[tracing::instrument]
+    pub async fn create(username: String) -> Result<Self, sqlx::Error> {
+        sqlx::query!(
+            "
+WITH new_token AS (SELECT encode(gen_random_bytes(32), 'hex') as value)
+INSERT INTO Sessions(user_id, refresh_token, expires_at)
+SELECT u.id, encode(digest(new_token.value, 'sha256'), 'hex'), NOW() + make_interval(days => $2)
+FROM new_token, Users as u
+WHERE u.username=$1
+RETURNING
+    id,
+    (SELECT username FROM Users WHERE Users.id=Sessions.user_id) as \"username!\",
+    (SELECT value FROM new_token) as refresh_token",
+            username,
+            SESSION_DAYS,
+        )
+        .map(|x| Self {
+            id: x.id,
+            username: x.username,
+            refresh_token: x.refresh_token,
+        })
+        .fetch_one(crate::database::get_db())
+        .await
+    }
+
+    /// Exchanges a refresh token for a new one, extending the session.
+    ///
+    /// A token that was already rotated is accepted during a short grace period without
+    /// issuing a new one; after that, presenting it revokes the whole session.
+    #[tracing::instrument(skip_all)]
+    pub async fn rotate(refresh_token: &str) -> Result<Option<Self>, sqlx::Error> {
+        let db = crate::database::get_db();
+        let rotated = sqlx::query!(
+            "
+WITH new_token AS (SELECT encode(gen_random_bytes(32), 'hex') as value)
+UPDATE Sessions SET
+    previous_refresh_token=refresh_token,
+    refresh_token=encode(digest(new_token.value, 'sha256'), 'hex'),
+    rotated_at=NOW(),
+    expires_at=NOW() + make_interval(days => $2)
+FROM new_token
+WHERE refresh_token=encode(digest($1, 'sha256'), 'hex') AND revoked_at IS NULL AND expires_at > NOW()
+RETURNING
+    id,
+    (SELECT username FROM Users WHERE Users.id=Sessions.user_id) as \"username!\",
+    new_token.value as refresh_token",
+            refresh_token,
+            SESSION_DAYS,
+        )
+        .map(|x| Self {
+            id: x.id,
+            username: x.username,
+            refresh_token: x.refresh_token,
+        })
+        .fetch_optional(db)
+        .await?;
+        if rotated.is_some() {
+            return Ok(rotated);
+        }
+
+        let previous = sqlx::query!(
+            "
+SELECT s.id, u.username, s.rotated_at > NOW() - make_interval(secs => $2) as in_grace
+FROM Sessions as s
+    JOIN Users as u ON u.id = s.user_id
+WHERE previous_refresh_token=encode(digest($1, 'sha256'), 'hex') AND revoked_at IS NULL AND expires_at > NOW()",
+            refresh_token,
+            ROTATION_GRACE_SECONDS,
+        )
+        .fetch_optional(db)
+        .await?;
+        match previous {
+            Some(x) if x.in_grace.unwrap_or_default() => Ok(Some(Self {
+                id: x.id,
+                username: x.username,
+                refresh_token: None,
+            })),
+            Some(x) => {
+                tracing::warn!(x.username, "refresh token reused, revoking its session");
+                Self::revoke(x.id).await?;
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub async fn is_active(id: i64, username: &str) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar!(
+            "
+SELECT EXISTS(
+    SELECT 1 FROM Sessions as s JOIN Users as u ON u.id = s.user_id
+    WHERE s.id=$1 and u.username=$2 and s.revoked_at IS NULL and s.expires_at > NOW()
+)",
+            id,
+            username,
+        )
+        .fetch_one(crate::database::get_db())
+        .await
+        .map(Option::unwrap_or_default)
+    }
+
+    pub async fn revoke(id: i64) -> Result<sqlx::postgres::PgQueryResult, sqlx::Error> {
+        sqlx::query!(
+            "UPDATE Sessions SET revoked_at=NOW() WHERE id=$1 and revoked_at IS NULL",
+            id
+        )
+        .execute(crate::database::get_db())
+        .await
+    }
+
+    /// Logs the user out from every device, it can run as part of a bigger transaction.
+    pub async fn revoke_all(
+        executor: impl sqlx::PgExecutor<'_>,
+        username: String,
+    ) -> Result<sqlx::postgres::PgQueryResult, sqlx::Error> {
+        sqlx::query!(
+            "
+UPDATE Sessions SET revoked_at=NOW()
+WHERE user_id=(SELECT id FROM Users WHERE username=$1) and revoked_at IS NULL",
+            username
+        )
+        .execute(executor)
+        .await
+    }
+}
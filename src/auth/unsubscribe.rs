@@ -0,0 +1,57 @@
+use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+
+const PURPOSE: &str = "digest_unsubscribe";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct UnsubscribeClaims {
+    /// Id of the user, so renaming them doesn't break the links already sent.
+    sub: i64,
+    purpose: String,
+}
+
+/// Stops the digest emails from the link they carry, without logging in.
+pub(crate) struct DigestUnsubscribe;
+
+impl DigestUnsubscribe {
+    /// The link doesn't expire, an old digest must still be able to stop the new ones.
+    pub fn link(host: &str, user_id: i64) -> String {
+        let token = jsonwebtoken::encode(
+            &Header::default(),
+            &UnsubscribeClaims {
+                sub: user_id,
+                purpose: PURPOSE.to_string(),
+            },
+            &EncodingKey::from_secret(super::server::jwt_secret().as_bytes()),
+        )
+        .expect("unsubscribe claims are always serializable");
+        crate::mailer::site_link(host, &format!("/unsubscribe?token={token}"))
+    }
+
+    /// Turns off the digest of the user the token was signed for.
+    ///
+    /// Returns the affected user, or `None` if the token is not valid.
+    #[tracing::instrument(skip_all)]
+    pub async fn unsubscribe(token: String) -> Result<Option<String>, sqlx::Error> {
+        let mut validation = Validation::default();
+        validation.required_spec_claims.clear();
+        validation.validate_exp = false;
+        let Ok(claims) = jsonwebtoken::decode::<UnsubscribeClaims>(
+            &token,
+            &DecodingKey::from_secret(super::server::jwt_secret().as_bytes()),
+            &validation,
+        )
+        .map(|x| x.claims) else {
+            return Ok(None);
+        };
+        if claims.purpose != PURPOSE {
+            return Ok(None);
+        }
+        sqlx::query_scalar!(
+            "UPDATE Users SET digest_frequency='never' WHERE id=$1 RETURNING username",
+            claims.sub,
+        )
+        .fetch_optional(crate::database::get_db())
+        .await
+    }
+}
@@ -8,14 +8,14 @@ pub type ArticleSignal = RwSignal<crate::models::Article>;
 #[component]
 pub fn ArticlePreviewList(
     username: crate::auth::UsernameSignal,
-    articles: Resource<Vec<crate::models::Article>>,
+    articles: Resource<crate::models::Page<crate::models::Article>>,
 ) -> impl IntoView {
     let articles_view = move || {
         articles.try_with(move |x| {
             x.clone().map(move |res| {
                 view! {
                     <For
-                        each=move || res.clone().into_iter()
+                        each=move || res.items.clone().into_iter()
                         key=|article| article.slug.clone()
                         children=move |article: crate::models::Article| {
                             let article = RwSignal::new(article);
@@ -49,7 +49,13 @@ fn ArticlePreview(username: crate::auth::UsernameSignal, article: ArticleSignal)
                 <A href=move || format!("/article/{}", article.with(|x| x.slug.clone()))>
                     <h1>{move || article.with(|x| x.title.clone())}</h1>
                     <p>{move || article.with(|x| x.description.clone())}</p>
+                    {move || article.with(|x| x.snippet.clone()).map(|snippet| view! {
+                        <p class="search-snippet" inner_html=snippet></p>
+                    })}
                     <span class="btn">"Read more..."</span>
+                    <span class="comments-count">
+                        <i class="ion-chatbubbles"></i>" "{move || article.with(|x| x.comments_count)}
+                    </span>
                     <Show
                         when=move || article.with(|x| !x.tag_list.is_empty())
                         fallback=|| view! {<span>"No tags"</span>}
@@ -79,6 +85,7 @@ pub fn ArticleMeta(
 ) -> impl IntoView {
     let editor_ref = move || format!("/editor/{}", article.with(|x| x.slug.clone()));
     let profile_ref = move || format!("/profile/{}", article.with(|x| x.author.username.clone()));
+    let history_ref = move || format!("/article/{}/history", article.with(|x| x.slug.clone()));
 
     let delete_a = ServerAction::<DeleteArticleAction>::new();
 
@@ -88,6 +95,12 @@ pub fn ArticleMeta(
             <div class="info">
                 <A href=profile_ref><span class="author">{move || article.with(|x| x.author.username.clone())}</span></A>
                 <span class="date">{move || article.with(|x| x.created_at.clone())}</span>
+                {move || article.with(|x| x.updated_at.clone()).map(|updated_at| view! {
+                    <A href=history_ref><span class="date">"Edited "{updated_at}</span></A>
+                })}
+                {move || article.with(|x| x.status.label()).map(|label| view! {
+                    <span class="tag-default tag-pill">{label}</span>
+                })}
             </div>
             <Show
                 when=move || is_preview
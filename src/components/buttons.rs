@@ -8,42 +8,60 @@ pub async fn follow_action(other_user: String) -> Result<bool, ServerFnError> {
             "You need to be authenticated".into(),
         ));
     };
-    toggle_follow(username, other_user).await.map_err(|x| {
-        tracing::error!("problem while updating the database: {x:?}");
-        ServerFnError::ServerError("error while updating the follow".into())
-    })
+    toggle_follow(username, other_user, None)
+        .await
+        .map_err(|x| {
+            tracing::error!("problem while updating the database: {x:?}");
+            ServerFnError::ServerError("error while updating the follow".into())
+        })
 }
 
+/// Switches whether `current` follows `other`, or sets it to `follow` when given, and notifies
+/// `other` of the change. Returns whether `current` follows `other` afterwards.
 #[cfg(feature = "ssr")]
 #[tracing::instrument]
-async fn toggle_follow(current: String, other: String) -> Result<bool, sqlx::Error> {
-    let db = crate::database::get_db();
-    match sqlx::query!(
-        "SELECT * FROM Follows WHERE follower=$1 and influencer=$2",
+pub(crate) async fn toggle_follow(
+    current: String,
+    other: String,
+    follow: Option<bool>,
+) -> Result<bool, sqlx::Error> {
+    let mut transaction = crate::database::get_db().begin().await?;
+    let following = match sqlx::query!(
+        "SELECT f.* FROM Follows as f, Users as fu, Users as iu WHERE f.follower_id=fu.id and f.influencer_id=iu.id and fu.username=$1 and iu.username=$2",
         current,
         other
     )
-    .fetch_one(db)
+    .fetch_one(transaction.as_mut())
     .await
     {
-        Ok(_) => sqlx::query!(
-            "DELETE FROM Follows WHERE follower=$1 and influencer=$2",
-            current,
-            other
-        )
-        .execute(db)
-        .await
-        .map(|_| false),
-        Err(sqlx::error::Error::RowNotFound) => sqlx::query!(
-            "INSERT INTO Follows(follower, influencer) VALUES ($1, $2)",
-            current,
-            other
-        )
-        .execute(db)
-        .await
-        .map(|_| true),
-        Err(x) => Err(x),
-    }
+        Ok(_) if follow == Some(true) => true,
+        Ok(_) => {
+            sqlx::query!(
+                "DELETE FROM Follows WHERE follower_id=(SELECT id FROM Users WHERE username=$1) and influencer_id=(SELECT id FROM Users WHERE username=$2)",
+                current,
+                other
+            )
+            .execute(transaction.as_mut())
+            .await?;
+            crate::models::Notification::unfollowed(transaction.as_mut(), &current, &other).await?;
+            false
+        }
+        Err(sqlx::error::Error::RowNotFound) if follow == Some(false) => false,
+        Err(sqlx::error::Error::RowNotFound) => {
+            sqlx::query!(
+                "INSERT INTO Follows(follower_id, influencer_id) SELECT f.id, i.id FROM Users as f, Users as i WHERE f.username=$1 and i.username=$2",
+                current,
+                other
+            )
+            .execute(transaction.as_mut())
+            .await?;
+            crate::models::Notification::followed(transaction.as_mut(), &current, &other).await?;
+            true
+        }
+        Err(x) => return Err(x),
+    };
+    transaction.commit().await?;
+    Ok(following)
 }
 
 #[component]
@@ -99,42 +117,63 @@ pub async fn fav_action(slug: String) -> Result<bool, ServerFnError> {
             "You need to be authenticated".into(),
         ));
     };
-    toggle_fav(slug, username).await.map_err(|x| {
+    toggle_fav(slug, username, None).await.map_err(|x| {
         tracing::error!("problem while updating the database: {x:?}");
         ServerFnError::ServerError("error while updating the follow".into())
     })
 }
 
+/// Switches whether the user has the article as favorite, or sets it to `fav` when given, and
+/// notifies its author of the change. Returns whether it is a favorite afterwards.
 #[cfg(feature = "ssr")]
 #[tracing::instrument]
-async fn toggle_fav(slug: String, username: String) -> Result<bool, sqlx::Error> {
-    let db = crate::database::get_db();
-    match sqlx::query!(
-        "SELECT * FROM FavArticles WHERE article=$1 and username=$2",
+pub(crate) async fn toggle_fav(
+    slug: String,
+    username: String,
+    fav: Option<bool>,
+) -> Result<bool, sqlx::Error> {
+    let mut transaction = crate::database::get_db().begin().await?;
+    let result = match sqlx::query!(
+        "SELECT fa.* FROM FavArticles as fa, Articles as a, Users as u WHERE fa.article_id=a.id and fa.user_id=u.id and a.slug=$1 and u.username=$2",
         slug,
         username
     )
-    .fetch_one(db)
+    .fetch_one(transaction.as_mut())
     .await
     {
-        Ok(_) => sqlx::query!(
-            "DELETE FROM FavArticles WHERE article=$1 and username=$2",
-            slug,
-            username
-        )
-        .execute(db)
-        .await
-        .map(|_| false),
-        Err(sqlx::error::Error::RowNotFound) => sqlx::query!(
-            "INSERT INTO FavArticles(article, username) VALUES ($1, $2)",
-            slug,
-            username
-        )
-        .execute(db)
-        .await
-        .map(|_| true),
-        Err(x) => Err(x),
-    }
+        Ok(_) if fav == Some(true) => true,
+        Ok(_) => {
+            sqlx::query!(
+                "DELETE FROM FavArticles WHERE article_id=(SELECT id FROM Articles WHERE slug=$1) and user_id=(SELECT id FROM Users WHERE username=$2)",
+                slug,
+                username
+            )
+            .execute(transaction.as_mut())
+            .await?;
+            crate::models::Notification::unfavorited(transaction.as_mut(), &username, &slug)
+                .await?;
+            false
+        }
+        Err(sqlx::error::Error::RowNotFound) if fav == Some(false) => false,
+        Err(sqlx::error::Error::RowNotFound) => {
+            // Drafts and scheduled articles can't be favorited, even knowing their slug
+            let inserted = sqlx::query!(
+                "INSERT INTO FavArticles(article_id, user_id) SELECT a.id, u.id FROM Articles as a, Users as u WHERE a.slug=$1 and a.status='published' and u.username=$2",
+                slug,
+                username
+            )
+            .execute(transaction.as_mut())
+            .await?;
+            if inserted.rows_affected() == 0 {
+                return Err(sqlx::Error::RowNotFound);
+            }
+            crate::models::Notification::favorited(transaction.as_mut(), &username, &slug).await?;
+            true
+        }
+        Err(x) => return Err(x),
+    };
+    transaction.commit().await?;
+    Ok(result)
 }
 
 #[component]
@@ -144,23 +183,35 @@ pub fn ButtonFav(
 ) -> impl IntoView {
     let make_fav = ServerAction::<FavAction>::new();
     let result_make_fav = make_fav.value();
-    let fav_count = move || {
-        if let Some(x) = result_make_fav.get() {
-            match x {
-                Ok(result) => {
-                    article.try_update(move |x| {
-                        x.fav = !x.fav;
-                        x.favorites_count =
-                            (x.favorites_count + if result { 1 } else { -1 }).max(0);
-                    });
-                }
-                Err(err) => {
-                    tracing::error!("problem while fav {err:?}");
+    // Pages with live events take the count from them, they include our own changes
+    let live = use_context::<crate::live::LiveSignal>();
+    Effect::new(move |_| match result_make_fav.get() {
+        Some(Ok(result)) => {
+            article.try_update(move |x| {
+                x.fav = result;
+                if live.is_none() {
+                    x.favorites_count = (x.favorites_count + if result { 1 } else { -1 }).max(0);
                 }
-            }
+            });
         }
-        article.try_with(|x| x.favorites_count).unwrap_or_default()
-    };
+        Some(Err(err)) => {
+            tracing::error!("problem while fav {err:?}");
+        }
+        None => {}
+    });
+    if let Some(live) = live {
+        Effect::new(move |_| {
+            if let Some(crate::live::LiveEvent::Favorites {
+                article: slug,
+                count,
+            }) = live.get()
+                && article.with_untracked(|x| x.slug == slug)
+            {
+                article.try_update(|x| x.favorites_count = count);
+            }
+        });
+    }
+    let fav_count = move || article.try_with(|x| x.favorites_count).unwrap_or_default();
 
     view! {
         <Show
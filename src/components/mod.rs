@@ -3,4 +3,6 @@ mod buttons;
 mod navitems;
 pub(crate) use article_preview::{ArticleMeta, ArticlePreviewList, ArticleSignal};
 pub(crate) use buttons::ButtonFollow;
+#[cfg(feature = "ssr")]
+pub(crate) use buttons::{toggle_fav, toggle_follow};
 pub(crate) use navitems::NavItems;
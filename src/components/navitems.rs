@@ -1,11 +1,30 @@
 use crate::auth::{LogoutSignal, UsernameSignal};
 use leptos::prelude::*;
-use leptos_router::components::A;
+use leptos_router::components::{A, Form};
+use leptos_router::hooks::use_location;
 
 #[component]
-pub(crate) fn NavItems(logout: LogoutSignal, username: UsernameSignal) -> impl IntoView {
+pub(crate) fn NavItems(
+    logout: LogoutSignal,
+    username: UsernameSignal,
+    mark_read: ServerAction<crate::routes::MarkNotificationsReadAction>,
+) -> impl IntoView {
     let profile_label = move || username.get().unwrap_or_default();
     let profile_href = move || format!("/profile/{}", profile_label());
+    let pathname = use_location().pathname;
+    let mark_read_version = mark_read.version();
+    // Checked again on every navigation
+    let unread = Resource::new(
+        move || (username.get(), pathname.get(), mark_read_version.get()),
+        |(username, _, _)| async move {
+            match username {
+                Some(_) => crate::routes::unread_notifications()
+                    .await
+                    .unwrap_or_default(),
+                None => 0,
+            }
+        },
+    );
 
     view! {
         <li class="nav-item">
@@ -38,5 +57,22 @@ pub(crate) fn NavItems(logout: LogoutSignal, username: UsernameSignal) -> impl I
                 <A href="/login"><span class="nav-link"><i class="ion-log-in"></i>" Login"</span></A>
             </li>
         </Show>
+        <li class="nav-item">
+            <Form action="/search">
+                <input class="form-control form-control-sm" type="search" name="q" placeholder="Search articles" required />
+            </Form>
+        </li>
+        <Show when=move || username.with(Option::is_some) fallback=|| ()>
+            <li class="nav-item">
+                <A href="/notifications"><span class="nav-link">
+                    <i class="ion-android-notifications"></i>
+                    <Transition fallback=|| ()>
+                        {move || unread.get().filter(|x| *x > 0).map(|x| view! {
+                            " "<span class="tag-pill tag-default">{x}</span>
+                        })}
+                    </Transition>
+                </span></A>
+            </li>
+        </Show>
     }
 }
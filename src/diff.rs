@@ -0,0 +1,75 @@
+//! Line diffs between two texts, from their longest common subsequence of lines.
+use crate::models::DiffLine;
+
+/// Lines compared at most by the quadratic search, bigger changes are shown as a whole
+/// replacement instead of eating the memory of the server.
+const MAX_COMPARISONS: usize = 4_000_000;
+
+pub fn lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+    // Edits tend to be small, so the common ends are left out of the search
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut diff = old[..prefix]
+        .iter()
+        .map(|x| DiffLine::Same((*x).to_string()))
+        .collect::<Vec<_>>();
+    diff.extend(middle(
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    ));
+    diff.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|x| DiffLine::Same((*x).to_string())),
+    );
+    diff
+}
+
+fn middle(old: &[&str], new: &[&str]) -> Vec<DiffLine> {
+    let (n, m) = (old.len(), new.len());
+    let mut diff = Vec::with_capacity(n + m);
+    if n.saturating_mul(m) > MAX_COMPARISONS {
+        diff.extend(old.iter().map(|x| DiffLine::Removed((*x).to_string())));
+        diff.extend(new.iter().map(|x| DiffLine::Added((*x).to_string())));
+        return diff;
+    }
+
+    // Length of the longest common subsequence of `old[i..]` and `new[j..]`
+    let at = move |i: usize, j: usize| i * (m + 1) + j;
+    let mut lcs = vec![0u32; (n + 1) * (m + 1)];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[at(i, j)] = if old[i] == new[j] {
+                lcs[at(i + 1, j + 1)] + 1
+            } else {
+                lcs[at(i + 1, j)].max(lcs[at(i, j + 1)])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old[i] == new[j] {
+            diff.push(DiffLine::Same(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if lcs[at(i + 1, j)] >= lcs[at(i, j + 1)] {
+            diff.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            diff.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        }
+    }
+    diff.extend(old[i..].iter().map(|x| DiffLine::Removed((*x).to_string())));
+    diff.extend(new[j..].iter().map(|x| DiffLine::Added((*x).to_string())));
+    diff
+}
@@ -0,0 +1,223 @@
+//! Downloads with everything a user has stored in the site, linked from the settings page.
+//!
+//! They are plain axum handlers because server functions can't answer with a file, the auth
+//! middleware already sends anonymous users under `/settings` to the login page.
+use std::io::Write;
+
+use axum::{
+    Router,
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use serde::Serialize;
+use sqlx::types::chrono::{DateTime, SecondsFormat, Utc};
+
+pub fn router<S: Clone + Send + Sync + 'static>() -> Router<S> {
+    Router::new()
+        .route("/settings/export.json", get(download_json))
+        .route("/settings/export.zip", get(download_zip))
+}
+
+#[derive(Serialize)]
+struct Export {
+    profile: crate::models::User,
+    articles: Vec<ExportedArticle>,
+    comments: Vec<ExportedComment>,
+    /// Slugs of the favorited articles.
+    favorites: Vec<String>,
+    /// Usernames of the followed users.
+    follows: Vec<String>,
+    exported_at: String,
+}
+
+#[derive(Serialize)]
+struct ExportedArticle {
+    slug: String,
+    title: String,
+    description: String,
+    body: String,
+    tag_list: Vec<String>,
+    status: String,
+    published_at: Option<String>,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(Serialize)]
+struct ExportedComment {
+    article: String,
+    body: String,
+    created_at: String,
+    edited_at: Option<String>,
+}
+
+fn format_date(date: DateTime<Utc>) -> String {
+    date.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+impl Export {
+    #[tracing::instrument]
+    async fn load(username: String) -> Result<Self, sqlx::Error> {
+        let db = crate::database::get_db();
+        let profile = crate::models::User::get(username.clone()).await?;
+        let articles = sqlx::query!(
+            "
+SELECT
+    a.slug, a.title, a.description, a.body, a.status, a.published_at, a.created_at, a.updated_at,
+    (SELECT array_agg(tag ORDER BY tag) FROM ArticleTags WHERE article_id = a.id) as tag_list
+FROM Articles as a
+    JOIN Users as u ON u.id = a.author_id
+WHERE u.username=$1
+ORDER BY a.created_at",
+            username
+        )
+        .map(|x| ExportedArticle {
+            slug: x.slug,
+            title: x.title,
+            description: x.description,
+            body: x.body,
+            tag_list: x.tag_list.unwrap_or_default(),
+            status: x.status,
+            published_at: x.published_at.map(format_date),
+            created_at: format_date(x.created_at),
+            updated_at: format_date(x.updated_at),
+        })
+        .fetch_all(db)
+        .await?;
+        let comments = sqlx::query!(
+            "
+SELECT a.slug, c.body, c.created_at, c.edited_at
+FROM Comments as c
+    JOIN Articles as a ON a.id = c.article_id
+    JOIN Users as u ON u.id = c.user_id
+WHERE u.username=$1
+ORDER BY c.created_at",
+            username
+        )
+        .map(|x| ExportedComment {
+            article: x.slug,
+            body: x.body,
+            created_at: format_date(x.created_at),
+            edited_at: x.edited_at.map(format_date),
+        })
+        .fetch_all(db)
+        .await?;
+        let favorites = sqlx::query_scalar!(
+            "
+SELECT a.slug
+FROM FavArticles as fa
+    JOIN Articles as a ON a.id = fa.article_id
+    JOIN Users as u ON u.id = fa.user_id
+WHERE u.username=$1
+ORDER BY a.slug",
+            username
+        )
+        .fetch_all(db)
+        .await?;
+        let follows = sqlx::query_scalar!(
+            "
+SELECT i.username
+FROM Follows as f
+    JOIN Users as i ON i.id = f.influencer_id
+    JOIN Users as u ON u.id = f.follower_id
+WHERE u.username=$1
+ORDER BY i.username",
+            username
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(Self {
+            profile,
+            articles,
+            comments,
+            favorites,
+            follows,
+            exported_at: format_date(Utc::now()),
+        })
+    }
+
+    /// ZIP with the whole export as `data.json` and every article as a Markdown file.
+    fn archive(&self) -> zip::result::ZipResult<Vec<u8>> {
+        let mut archive = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default();
+        archive.start_file("data.json", options)?;
+        archive.write_all(&serde_json::to_vec_pretty(self).map_err(std::io::Error::from)?)?;
+        for article in &self.articles {
+            archive.start_file(format!("articles/{}.md", article.slug), options)?;
+            archive.write_all(article.markdown().as_bytes())?;
+        }
+        Ok(archive.finish()?.into_inner())
+    }
+}
+
+impl ExportedArticle {
+    /// The body with the rest of the fields as front matter, JSON strings are valid YAML.
+    fn markdown(&self) -> String {
+        let quote = |x: &str| serde_json::to_string(x).unwrap_or_default();
+        format!(
+            "---\ntitle: {}\ndescription: {}\ntags: [{}]\nstatus: {}\npublished_at: {}\ncreated_at: {}\nupdated_at: {}\n---\n\n{}\n",
+            quote(&self.title),
+            quote(&self.description),
+            self.tag_list
+                .iter()
+                .map(|x| quote(x))
+                .collect::<Vec<_>>()
+                .join(", "),
+            self.status,
+            self.published_at.as_deref().unwrap_or("null"),
+            self.created_at,
+            self.updated_at,
+            self.body,
+        )
+    }
+}
+
+async fn load(headers: &HeaderMap) -> Result<Export, StatusCode> {
+    let username =
+        crate::auth::get_username_from_headers(headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    Export::load(username).await.map_err(|x| {
+        tracing::error!("problem while exporting the user data: {x:?}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+fn attachment(content_type: &'static str, disposition: &'static str, body: Vec<u8>) -> Response {
+    (
+        [
+            (header::CONTENT_TYPE, content_type),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+#[tracing::instrument(skip_all)]
+async fn download_json(headers: HeaderMap) -> Result<Response, StatusCode> {
+    let export = load(&headers).await?;
+    let body = serde_json::to_vec_pretty(&export).map_err(|x| {
+        tracing::error!("problem while serializing the export: {x:?}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(attachment(
+        "application/json",
+        "attachment; filename=\"conduit.json\"",
+        body,
+    ))
+}
+
+#[tracing::instrument(skip_all)]
+async fn download_zip(headers: HeaderMap) -> Result<Response, StatusCode> {
+    let export = load(&headers).await?;
+    let body = export.archive().map_err(|x| {
+        tracing::error!("problem while building the export archive: {x:?}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(attachment(
+        "application/zip",
+        "attachment; filename=\"conduit.zip\"",
+        body,
+    ))
+}
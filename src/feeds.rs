@@ -0,0 +1,247 @@
+//! Atom feeds of the published articles for feed readers: the global one, by tag and by author.
+//!
+//! Readers poll them, so they are answered with `304 Not Modified` while nothing changed.
+use axum::{
+    Router,
+    extract::Path,
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Redirect, Response},
+    routing::get,
+};
+use axum_extra::extract::Host;
+use sqlx::types::chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::models::Article;
+
+/// Latest articles listed in every feed.
+const ENTRIES: i64 = 20;
+
+pub fn router<S: Clone + Send + Sync + 'static>() -> Router<S> {
+    Router::new()
+        .route("/feed.xml", get(global_feed))
+        .route("/tag/{tag}/feed.xml", get(tag_feed))
+        .route("/profile/{user}/feed.xml", get(profile_feed))
+}
+
+/// Escapes text for the content and the attributes of the XML elements.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Percent-encodes everything but the unreserved characters, so the text can go in a path
+/// segment or a query value.
+fn encode(text: &str) -> String {
+    text.bytes()
+        .map(|x| match x {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                char::from(x).to_string()
+            }
+            x => format!("%{x:02X}"),
+        })
+        .collect()
+}
+
+/// 64-bit FNV-1a of the text, which unlike `DefaultHasher` doesn't change between Rust
+/// releases, so the `ETag`s stay valid across deploys.
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, x| {
+        (hash ^ u64::from(x)).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn format_date(date: DateTime<Utc>) -> String {
+    date.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn format_timestamp(timestamp: i64) -> String {
+    format_date(DateTime::from_timestamp(timestamp, 0).unwrap_or_default())
+}
+
+/// A feed with the articles, as listed in the page at `page`.
+struct Feed<'a> {
+    title: String,
+    /// Path of the page listing the same articles.
+    page: String,
+    /// Path of the feed itself.
+    path: String,
+    articles: &'a [Article],
+}
+
+impl Feed<'_> {
+    /// Latest change of the articles, `None` without articles.
+    fn updated(&self) -> Option<DateTime<Utc>> {
+        self.articles
+            .iter()
+            .map(|x| x.updated_ts)
+            .max()
+            .and_then(|x| DateTime::from_timestamp(x, 0))
+    }
+
+    fn render(&self, host: &str) -> String {
+        let link = |path: &str| escape(&crate::mailer::site_link(host, path));
+        let mut xml = format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+    <title>{}</title>
+    <id>{}</id>
+    <link rel="self" type="application/atom+xml" href="{}"/>
+    <link rel="alternate" type="text/html" href="{}"/>
+    <updated>{}</updated>
+"#,
+            escape(&self.title),
+            link(&self.path),
+            link(&self.path),
+            link(&self.page),
+            format_date(self.updated().unwrap_or_default()),
+        );
+        for article in self.articles {
+            let article_link = link(&format!("/article/{}", article.slug));
+            xml.push_str(&format!(
+                r#"    <entry>
+        <title>{}</title>
+        <id>{article_link}</id>
+        <link rel="alternate" type="text/html" href="{article_link}"/>
+        <published>{}</published>
+        <updated>{}</updated>
+        <author>
+            <name>{}</name>
+            <uri>{}</uri>
+        </author>
+{}        <summary type="html">{}</summary>
+    </entry>
+"#,
+                escape(&article.title),
+                format_timestamp(article.published_ts),
+                format_timestamp(article.updated_ts),
+                escape(&article.author.username),
+                link(&format!("/profile/{}", encode(&article.author.username))),
+                article
+                    .tag_list
+                    .iter()
+                    .filter(|x| !x.is_empty())
+                    .map(|x| format!("        <category term=\"{}\"/>\n", escape(x)))
+                    .collect::<String>(),
+                escape(&crate::markdown::render(&article.description)),
+            ));
+        }
+        xml.push_str("</feed>\n");
+        xml
+    }
+
+    /// The feed, or `304 Not Modified` when the reader already has it. The `ETag` covers the
+    /// whole document, so it also changes when an article leaves the feed.
+    fn respond(&self, host: &str, headers: &HeaderMap) -> Response {
+        let body = self.render(host);
+        let etag = format!("\"{:016x}\"", fnv1a(&body));
+        let updated = self.updated();
+
+        // If-Modified-Since is only checked when the reader doesn't send an ETag
+        let not_modified = match headers.get(header::IF_NONE_MATCH) {
+            Some(x) => x.to_str().is_ok_and(|x| {
+                x.split(',')
+                    .map(|x| x.trim().trim_start_matches("W/"))
+                    .any(|x| x == "*" || x == etag)
+            }),
+            None => headers
+                .get(header::IF_MODIFIED_SINCE)
+                .and_then(|x| x.to_str().ok())
+                .and_then(|x| DateTime::parse_from_rfc2822(x).ok())
+                .zip(updated)
+                .is_some_and(|(since, updated)| updated <= since.with_timezone(&Utc)),
+        };
+        let mut response = if not_modified {
+            StatusCode::NOT_MODIFIED.into_response()
+        } else {
+            (
+                [(header::CONTENT_TYPE, "application/atom+xml; charset=utf-8")],
+                body,
+            )
+                .into_response()
+        };
+        let response_headers = response.headers_mut();
+        if let Ok(x) = HeaderValue::from_str(&etag) {
+            response_headers.insert(header::ETAG, x);
+        }
+        if let Some(x) = updated.and_then(|x| {
+            HeaderValue::from_str(&x.format("%a, %d %b %Y %H:%M:%S GMT").to_string()).ok()
+        }) {
+            response_headers.insert(header::LAST_MODIFIED, x);
+        }
+        response
+    }
+}
+
+fn internal_error(x: sqlx::Error) -> StatusCode {
+    tracing::error!("problem while building the feed: {x:?}");
+    StatusCode::INTERNAL_SERVER_ERROR
+}
+
+#[tracing::instrument(skip(headers))]
+async fn global_feed(Host(host): Host, headers: HeaderMap) -> Result<Response, StatusCode> {
+    let page = Article::for_feed(None, ENTRIES, String::new(), false, None, None, None)
+        .await
+        .map_err(internal_error)?;
+    let feed = Feed {
+        title: "conduit".to_string(),
+        page: "/".to_string(),
+        path: "/feed.xml".to_string(),
+        articles: &page.items,
+    };
+    Ok(feed.respond(&host, &headers))
+}
+
+#[tracing::instrument(skip(headers))]
+async fn tag_feed(
+    Host(host): Host,
+    Path(tag): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    let page = Article::for_feed(None, ENTRIES, tag.clone(), false, None, None, None)
+        .await
+        .map_err(internal_error)?;
+    let feed = Feed {
+        title: format!("conduit: articles tagged {tag}"),
+        page: format!("/?tag={}", encode(&tag)),
+        path: format!("/tag/{}/feed.xml", encode(&tag)),
+        articles: &page.items,
+    };
+    Ok(feed.respond(&host, &headers))
+}
+
+/// Old usernames of renamed users redirect to the feed of the current one.
+#[tracing::instrument(skip(headers))]
+async fn profile_feed(
+    Host(host): Host,
+    Path(user): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    match crate::models::User::get(user.clone()).await {
+        Ok(_) => {}
+        Err(sqlx::Error::RowNotFound) => {
+            return match crate::models::User::resolve_alias(user).await {
+                Ok(Some(current)) => Ok(Redirect::permanent(&format!(
+                    "/profile/{}/feed.xml",
+                    encode(&current)
+                ))
+                .into_response()),
+                Ok(None) => Err(StatusCode::NOT_FOUND),
+                Err(x) => Err(internal_error(x)),
+            };
+        }
+        Err(x) => return Err(internal_error(x)),
+    }
+    let page = Article::for_user_profile(user.clone(), false, false, ENTRIES, None, None)
+        .await
+        .map_err(internal_error)?;
+    let feed = Feed {
+        title: format!("conduit: articles by {user}"),
+        page: format!("/profile/{}", encode(&user)),
+        path: format!("/profile/{}/feed.xml", encode(&user)),
+        articles: &page.items,
+    };
+    Ok(feed.respond(&host, &headers))
+}
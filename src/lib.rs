@@ -5,10 +5,27 @@ pub(crate) mod auth;
 pub(crate) mod components;
 #[cfg(feature = "ssr")]
 pub(crate) mod database;
+#[cfg(feature = "ssr")]
+pub(crate) mod diff;
+#[cfg(feature = "ssr")]
+pub(crate) mod export;
+#[cfg(feature = "ssr")]
+pub(crate) mod feeds;
+pub(crate) mod live;
+#[cfg(feature = "ssr")]
+pub(crate) mod mailer;
+#[cfg(feature = "ssr")]
+pub(crate) mod markdown;
 pub(crate) mod models;
+#[cfg(feature = "ssr")]
+pub(crate) mod rest;
 pub(crate) mod routes;
 #[cfg(feature = "ssr")]
+pub(crate) mod scheduler;
+#[cfg(feature = "ssr")]
 pub mod setup;
+#[cfg(feature = "ssr")]
+pub(crate) mod slug;
 
 #[cfg(feature = "hydrate")]
 #[wasm_bindgen::prelude::wasm_bindgen]
@@ -26,4 +43,5 @@ pub fn hydrate() {
 extern "C" {
     fn decodeJWT(token: String) -> String;
     fn emailRegex(email: &str) -> bool;
+    fn timezoneOffset(date: &str) -> i32;
 }
@@ -0,0 +1,126 @@
+//! Changes of an article pushed to its readers as they happen. The database notifies them on
+//! the `live` channel and the server forwards them as server-sent events.
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LiveEvent {
+    /// A comment was posted, edited or deleted, `count` doesn't include the deleted ones.
+    Comments { article: String, count: i64 },
+    /// The article was favorited or unfavorited.
+    Favorites { article: String, count: i64 },
+}
+
+impl LiveEvent {
+    pub fn article(&self) -> &str {
+        match self {
+            Self::Comments { article, .. } | Self::Favorites { article, .. } => article,
+        }
+    }
+}
+
+/// Latest event of the article being read, shared by the components of its page.
+pub type LiveSignal = ReadSignal<Option<LiveEvent>>;
+
+#[cfg(feature = "ssr")]
+static EVENTS: std::sync::OnceLock<tokio::sync::broadcast::Sender<LiveEvent>> =
+    std::sync::OnceLock::new();
+
+/// Readers lagging this many events behind skip them, the next ones carry the latest counts.
+#[cfg(feature = "ssr")]
+const CAPACITY: usize = 256;
+
+/// Waiting time before listening again when the connection to the database is lost.
+#[cfg(feature = "ssr")]
+const RETRY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Forwards the notifications of the database to the connected readers.
+#[cfg(feature = "ssr")]
+pub fn spawn_listener() {
+    let (sender, _) = tokio::sync::broadcast::channel(CAPACITY);
+    EVENTS
+        .set(sender.clone())
+        .expect("live listener already spawned");
+    tokio::spawn(async move {
+        loop {
+            if let Err(x) = listen(&sender).await {
+                tracing::error!("problem while listening to the live events: {x:?}");
+            }
+            tokio::time::sleep(RETRY_INTERVAL).await;
+        }
+    });
+}
+
+#[cfg(feature = "ssr")]
+async fn listen(sender: &tokio::sync::broadcast::Sender<LiveEvent>) -> Result<(), sqlx::Error> {
+    let mut listener = sqlx::postgres::PgListener::connect_with(crate::database::get_db()).await?;
+    listener.listen("live").await?;
+    loop {
+        let notification = listener.recv().await?;
+        match serde_json::from_str::<LiveEvent>(notification.payload()) {
+            Ok(event) => {
+                // Nobody reading is fine
+                let _ = sender.send(event);
+            }
+            Err(x) => tracing::error!("invalid live event {:?}: {x}", notification.payload()),
+        }
+    }
+}
+
+#[cfg(feature = "ssr")]
+pub fn router<S: Clone + Send + Sync + 'static>() -> axum::Router<S> {
+    axum::Router::new().route("/live/article/{slug}", axum::routing::get(article_events))
+}
+
+#[cfg(feature = "ssr")]
+#[tracing::instrument]
+async fn article_events(
+    axum::extract::Path(slug): axum::extract::Path<String>,
+) -> axum::response::sse::Sse<
+    impl tokio_stream::Stream<Item = Result<axum::response::sse::Event, std::convert::Infallible>>,
+> {
+    use tokio_stream::StreamExt;
+
+    let receiver = EVENTS.get().expect("live listener not spawned").subscribe();
+    let events = tokio_stream::wrappers::BroadcastStream::new(receiver).filter_map(move |event| {
+        let event = event.ok().filter(|x| x.article() == slug)?;
+        axum::response::sse::Event::default()
+            .json_data(event)
+            .ok()
+            .map(Ok)
+    });
+    axum::response::sse::Sse::new(events).keep_alive(axum::response::sse::KeepAlive::default())
+}
+
+/// Events of the article while the calling component lives, they only come once the page is
+/// hydrated.
+pub fn subscribe(slug: String) -> LiveSignal {
+    let (event, set_event) = signal(None);
+    #[cfg(feature = "hydrate")]
+    Effect::new(move |_| {
+        use wasm_bindgen::{JsCast, closure::Closure};
+
+        let Ok(source) = web_sys::EventSource::new(&format!("/live/article/{slug}")) else {
+            tracing::error!("could not subscribe to the live events of {slug}");
+            return;
+        };
+        let on_message = Closure::<dyn FnMut(web_sys::MessageEvent)>::new(
+            move |message: web_sys::MessageEvent| {
+                let Some(data) = message.data().as_string() else {
+                    return;
+                };
+                match serde_json::from_str::<LiveEvent>(&data) {
+                    Ok(x) => set_event.set(Some(x)),
+                    Err(x) => tracing::error!("invalid live event {data:?}: {x}"),
+                }
+            },
+        );
+        source.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        let subscription = send_wrapper::SendWrapper::new((source, on_message));
+        on_cleanup(move || subscription.take().0.close());
+    });
+    #[cfg(not(feature = "hydrate"))]
+    let _ = (slug, set_event);
+    event
+}
@@ -0,0 +1,219 @@
+//! Outgoing emails. The transport is picked through `MAILER_TRANSPORT` so features that send
+//! emails can be exercised locally and in tests without an SMTP server.
+//!
+//! Features should not send emails directly but [`Email::enqueue`] them, see [`worker`].
+use std::{env, future::Future, path::PathBuf};
+
+use mail_send::mail_builder::MessageBuilder;
+
+mod templates;
+mod worker;
+
+pub use worker::{spawn_worker, wake_worker};
+
+static MAILER: std::sync::OnceLock<Transport> = std::sync::OnceLock::new();
+static DEFAULT_SENDER: &str = "noreply@realworld-leptos.local";
+static DEFAULT_OUTBOX_DIR: &str = "target/outbox";
+static DEFAULT_SITE_HOST: &str = "localhost:3000";
+
+#[derive(Debug, Clone)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub text_body: String,
+    pub html_body: String,
+}
+
+#[derive(Debug)]
+pub enum MailError {
+    Smtp(mail_send::Error),
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for MailError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Smtp(x) => write!(f, "smtp error: {x}"),
+            Self::Io(x) => write!(f, "io error: {x}"),
+        }
+    }
+}
+
+impl From<mail_send::Error> for MailError {
+    fn from(value: mail_send::Error) -> Self {
+        Self::Smtp(value)
+    }
+}
+
+impl From<std::io::Error> for MailError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+pub trait Mailer: Send + Sync {
+    fn send(&self, email: &Email) -> impl Future<Output = Result<(), MailError>> + Send;
+}
+
+/// Host of the site for the emails sent outside of a request, like the digests.
+pub fn site_host() -> String {
+    env::var("SITE_HOST").unwrap_or_else(|_| DEFAULT_SITE_HOST.to_string())
+}
+
+/// Absolute link to a page of the site, for emails, feeds and canonical links.
+pub fn site_link(host: &str, path: &str) -> String {
+    let schema = if cfg!(debug_assertions) {
+        "http"
+    } else {
+        "https"
+    };
+    format!("{schema}://{host}{path}")
+}
+
+fn build_message<'a>(sender: &'a str, email: &'a Email) -> MessageBuilder<'a> {
+    MessageBuilder::new()
+        .from(("Realworld Leptos", sender))
+        .to(vec![("You", email.to.as_str())])
+        .subject(email.subject.as_str())
+        .text_body(email.text_body.as_str())
+        .html_body(email.html_body.as_str())
+}
+
+pub struct SmtpMailer {
+    server: String,
+    port: u16,
+    email: String,
+    passwd: String,
+}
+
+impl SmtpMailer {
+    fn from_env(email: String) -> Self {
+        Self {
+            server: env::var("MAILER_SMTP_SERVER").expect("no MAILER_SMTP_SERVER specify"),
+            port: env::var("MAILER_SMTP_PORT")
+                .ok()
+                .and_then(|x| x.parse().ok())
+                .unwrap_or(587),
+            passwd: env::var("MAILER_PASSWD").expect("no MAILER_PASSWD specify"),
+            email,
+        }
+    }
+}
+
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: &Email) -> Result<(), MailError> {
+        // Connect to the SMTP submissions port, upgrade to TLS and
+        // authenticate using the provided credentials.
+        mail_send::SmtpClientBuilder::new(self.server.as_str(), self.port)
+            .implicit_tls(false)
+            .credentials((self.email.as_str(), self.passwd.as_str()))
+            .connect()
+            .await?
+            .send(build_message(&self.email, email))
+            .await?;
+        Ok(())
+    }
+}
+
+/// Prints the emails in the logs instead of sending them.
+pub struct LogMailer;
+
+impl Mailer for LogMailer {
+    async fn send(&self, email: &Email) -> Result<(), MailError> {
+        tracing::info!(
+            to = %email.to,
+            subject = %email.subject,
+            "email not sent, logging it instead:\n{}",
+            email.text_body
+        );
+        Ok(())
+    }
+}
+
+/// Writes every email as an `.eml` file into a folder, ready to be opened or parsed by tests.
+pub struct OutboxMailer {
+    sender: String,
+    dir: PathBuf,
+}
+
+impl Mailer for OutboxMailer {
+    async fn send(&self, email: &Email) -> Result<(), MailError> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let file_name = format!(
+            "{}-{}.eml",
+            sqlx::types::chrono::Utc::now().format("%Y%m%dT%H%M%S%.6f"),
+            email
+                .to
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect::<String>()
+        );
+        let message = build_message(&self.sender, email).write_to_vec()?;
+        tokio::fs::write(self.dir.join(file_name), message).await?;
+        Ok(())
+    }
+}
+
+pub enum Transport {
+    Smtp(SmtpMailer),
+    Log(LogMailer),
+    Outbox(OutboxMailer),
+}
+
+impl Transport {
+    /// # Panics
+    ///
+    /// Will panic if the SMTP transport is selected without its credentials
+    fn from_env() -> Self {
+        let sender = env::var("MAILER_EMAIL").unwrap_or_else(|_| DEFAULT_SENDER.to_string());
+        match env::var("MAILER_TRANSPORT").as_deref() {
+            Ok("smtp") => Self::Smtp(SmtpMailer::from_env(sender)),
+            Ok("outbox") => Self::Outbox(OutboxMailer {
+                sender,
+                dir: env::var("MAILER_OUTBOX_DIR")
+                    .unwrap_or_else(|_| DEFAULT_OUTBOX_DIR.to_string())
+                    .into(),
+            }),
+            Ok("log") => Self::Log(LogMailer),
+            Ok(x) => {
+                tracing::warn!("unknown MAILER_TRANSPORT {x}, emails will be logged");
+                Self::Log(LogMailer)
+            }
+            // Keep the previous behaviour when only the SMTP credentials are configured
+            Err(_) if env::var("MAILER_SMTP_SERVER").is_ok() => {
+                Self::Smtp(SmtpMailer::from_env(sender))
+            }
+            Err(_) => Self::Log(LogMailer),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Smtp(_) => "smtp",
+            Self::Log(_) => "log",
+            Self::Outbox(_) => "outbox",
+        }
+    }
+}
+
+impl Mailer for Transport {
+    async fn send(&self, email: &Email) -> Result<(), MailError> {
+        match self {
+            Self::Smtp(x) => x.send(email).await,
+            Self::Log(x) => x.send(email).await,
+            Self::Outbox(x) => x.send(email).await,
+        }
+    }
+}
+
+pub fn init_mailer() {
+    let transport = MAILER.get_or_init(Transport::from_env);
+    tracing::info!(
+        "emails are delivered through the {} transport",
+        transport.name()
+    );
+}
+
+pub fn get_mailer<'a>() -> &'a Transport {
+    MAILER.get_or_init(Transport::from_env)
+}
@@ -0,0 +1,156 @@
+use super::Email;
+
+fn layout(title: &str, content: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+    <head>
+        <meta charset="utf-8">
+        <title>{title}</title>
+    </head>
+    <body style="font-family: 'Source Sans Pro', sans-serif; color: #373a3c;">
+        <h1 style="color: #5cb85c; font-family: 'Titillium Web', sans-serif;">conduit</h1>
+        {content}
+        <p style="color: #bbb; font-size: 0.8rem;">A place to share your knowledge.</p>
+    </body>
+</html>"#
+    )
+}
+
+impl Email {
+    pub fn password_reset(to: String, username: &str, link: &str) -> Self {
+        let subject = String::from("Your password reset from realworld leptos");
+        let text_body = format!(
+            "Hi {username},\n\n\
+            You can reset your password accessing the following link: {link}\n\n\
+            It can only be used once and expires in an hour. \
+            If you didn't ask for it, you can ignore this email."
+        );
+        let html_body = layout(
+            &subject,
+            &format!(
+                r#"<p>Hi {},</p>
+        <p>You can reset your password accessing the following link:
+            <a href="{link}">{link}</a>
+        </p>
+        <p>It can only be used once and expires in an hour. If you didn't ask for it, you can ignore this email.</p>"#,
+                ammonia::clean_text(username),
+                link = ammonia::clean_text(link),
+            ),
+        );
+        Self {
+            to,
+            subject,
+            text_body,
+            html_body,
+        }
+    }
+
+    pub fn email_verification(to: String, username: &str, link: &str) -> Self {
+        let subject = String::from("Verify your email for realworld leptos");
+        let text_body = format!(
+            "Hi {username},\n\n\
+            Please, confirm this is your email accessing the following link: {link}\n\n\
+            It expires in two days, you can ask for a new one from your settings."
+        );
+        let html_body = layout(
+            &subject,
+            &format!(
+                r#"<p>Hi {},</p>
+        <p>Please, confirm this is your email accessing the following link:
+            <a href="{link}">{link}</a>
+        </p>
+        <p>It expires in two days, you can ask for a new one from your settings.</p>"#,
+                ammonia::clean_text(username),
+                link = ammonia::clean_text(link),
+            ),
+        );
+        Self {
+            to,
+            subject,
+            text_body,
+            html_body,
+        }
+    }
+
+    /// New articles of the followed authors, `more` tells there are others left out.
+    pub fn digest(
+        to: String,
+        username: &str,
+        articles: &[crate::models::Article],
+        more: bool,
+        host: &str,
+        unsubscribe_link: &str,
+    ) -> Self {
+        let subject = String::from("New articles from the authors you follow");
+        let more_text = if more {
+            format!(
+                "There are more in your feed: {}\n\n",
+                super::site_link(host, "/?my_feed=true")
+            )
+        } else {
+            String::new()
+        };
+        let text_body = format!(
+            "Hi {username},\n\n\
+            These are the new articles from the authors you follow:\n\n\
+            {}\n\
+            {more_text}\
+            You can stop these emails accessing the following link: {unsubscribe_link}",
+            articles
+                .iter()
+                .map(|x| format!(
+                    "- {} by {}: {}\n  {}\n",
+                    x.title,
+                    x.author.username,
+                    super::site_link(host, &format!("/article/{}", x.slug)),
+                    x.description,
+                ))
+                .collect::<String>(),
+        );
+        let html_body = layout(
+            &subject,
+            &format!(
+                r#"<p>Hi {},</p>
+        <p>These are the new articles from the authors you follow:</p>
+        <ul>{}</ul>
+        {}
+        <p style="font-size: 0.8rem;">You can stop these emails accessing the following link:
+            <a href="{unsubscribe_link}">{unsubscribe_link}</a>
+        </p>"#,
+                ammonia::clean_text(username),
+                articles
+                    .iter()
+                    .map(|x| format!(
+                        r#"<li>
+            <a href="{}">{}</a> by {}
+            <p>{}</p>
+        </li>"#,
+                        ammonia::clean_text(&super::site_link(
+                            host,
+                            &format!("/article/{}", x.slug)
+                        )),
+                        ammonia::clean_text(&x.title),
+                        ammonia::clean_text(&x.author.username),
+                        ammonia::clean_text(&x.description),
+                    ))
+                    .collect::<String>(),
+                if more {
+                    format!(
+                        r#"<p><a href="{0}">There are more in your feed</a></p>"#,
+                        ammonia::clean_text(&super::site_link(host, "/?my_feed=true"))
+                    )
+                } else {
+                    String::new()
+                },
+                unsubscribe_link = ammonia::clean_text(unsubscribe_link),
+            ),
+        );
+        Self {
+            to,
+            subject,
+            text_body,
+            html_body,
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Emails are stored in the `EmailOutbox` table by the same transaction that triggers them and
+//! delivered in the background, so a slow or failing transport neither blocks nor loses them.
+use std::time::Duration;
+
+use super::{Email, Mailer};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+const BATCH_SIZE: i64 = 10;
+/// Attempts before an email is dead-lettered.
+const MAX_ATTEMPTS: i32 = 8;
+const BASE_BACKOFF_SECONDS: f64 = 30.0;
+const MAX_BACKOFF_SECONDS: f64 = 6.0 * 60.0 * 60.0;
+/// Emails being delivered are hidden from other workers for this long, in case this one dies.
+const LEASE_SECONDS: f64 = 5.0 * 60.0;
+
+static WAKE: tokio::sync::Notify = tokio::sync::Notify::const_new();
+
+struct QueuedEmail {
+    id: i64,
+    attempts: i32,
+    email: Email,
+}
+
+impl Email {
+    /// Queues the email, it is only delivered if the surrounding transaction commits.
+    pub async fn enqueue(&self, executor: impl sqlx::PgExecutor<'_>) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            "INSERT INTO EmailOutbox(recipient, subject, text_body, html_body) VALUES ($1, $2, $3, $4) RETURNING id",
+            self.to,
+            self.subject,
+            self.text_body,
+            self.html_body,
+        )
+        .fetch_one(executor)
+        .await
+    }
+}
+
+/// Lets the worker know there are new emails instead of waiting for the next poll.
+pub fn wake_worker() {
+    WAKE.notify_one();
+}
+
+pub fn spawn_worker() {
+    tokio::spawn(async {
+        if let Err(x) = log_queue_depth().await {
+            tracing::error!("could not read the email outbox: {x:?}");
+        }
+        loop {
+            match deliver_batch().await {
+                // There may be more waiting
+                Ok(delivered) if delivered as i64 == BATCH_SIZE => continue,
+                Ok(_) => {}
+                Err(x) => tracing::error!("problem while delivering the email outbox: {x:?}"),
+            }
+            let _ = tokio::time::timeout(POLL_INTERVAL, WAKE.notified()).await;
+        }
+    });
+}
+
+fn backoff_seconds(attempts: i32) -> f64 {
+    (BASE_BACKOFF_SECONDS * 2f64.powi(attempts - 1)).min(MAX_BACKOFF_SECONDS)
+}
+
+async fn deliver_batch() -> Result<usize, sqlx::Error> {
+    let db = crate::database::get_db();
+    let batch = sqlx::query!(
+        "
+UPDATE EmailOutbox SET attempts=attempts+1, next_attempt_at=NOW() + make_interval(secs => $2)
+WHERE id IN (
+    SELECT id FROM EmailOutbox
+    WHERE dead_at IS NULL AND next_attempt_at <= NOW()
+    ORDER BY next_attempt_at
+    LIMIT $1
+    FOR UPDATE SKIP LOCKED
+)
+RETURNING id, attempts, recipient, subject, text_body, html_body",
+        BATCH_SIZE,
+        LEASE_SECONDS,
+    )
+    .map(|x| QueuedEmail {
+        id: x.id,
+        attempts: x.attempts,
+        email: Email {
+            to: x.recipient,
+            subject: x.subject,
+            text_body: x.text_body,
+            html_body: x.html_body,
+        },
+    })
+    .fetch_all(db)
+    .await?;
+
+    for queued in batch.iter() {
+        match super::get_mailer().send(&queued.email).await {
+            Ok(()) => {
+                // Bodies may carry one-time links, so sent emails are not kept around
+                sqlx::query!("DELETE FROM EmailOutbox WHERE id=$1", queued.id)
+                    .execute(db)
+                    .await?;
+            }
+            Err(x) if queued.attempts >= MAX_ATTEMPTS => {
+                tracing::error!(
+                    queued.id,
+                    "email dead-lettered after {} attempts: {x}",
+                    queued.attempts
+                );
+                sqlx::query!(
+                    "UPDATE EmailOutbox SET dead_at=NOW(), last_error=$2 WHERE id=$1",
+                    queued.id,
+                    x.to_string(),
+                )
+                .execute(db)
+                .await?;
+            }
+            Err(x) => {
+                let delay = backoff_seconds(queued.attempts);
+                tracing::warn!(
+                    queued.id,
+                    "email delivery failed, retrying in {delay}s: {x}"
+                );
+                sqlx::query!(
+                    "UPDATE EmailOutbox SET next_attempt_at=NOW() + make_interval(secs => $2), last_error=$3 WHERE id=$1",
+                    queued.id,
+                    delay,
+                    x.to_string(),
+                )
+                .execute(db)
+                .await?;
+            }
+        }
+    }
+    if !batch.is_empty() {
+        log_queue_depth().await?;
+    }
+    Ok(batch.len())
+}
+
+async fn log_queue_depth() -> Result<(), sqlx::Error> {
+    let depth = sqlx::query!(
+        "
+SELECT
+    COUNT(*) FILTER (WHERE dead_at IS NULL) as pending,
+    COUNT(*) FILTER (WHERE dead_at IS NOT NULL) as dead
+FROM EmailOutbox"
+    )
+    .fetch_one(crate::database::get_db())
+    .await?;
+    tracing::info!(
+        pending = depth.pending.unwrap_or_default(),
+        dead = depth.dead.unwrap_or_default(),
+        "email outbox depth"
+    );
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use pulldown_cmark::{Options, Parser, html};
+
+static SANITIZER: std::sync::OnceLock<ammonia::Builder<'static>> = std::sync::OnceLock::new();
+static SNIPPET_SANITIZER: std::sync::OnceLock<ammonia::Builder<'static>> =
+    std::sync::OnceLock::new();
+
+/// Renders user provided markdown into HTML that is safe to inject in the page.
+///
+/// Everything the markdown produces goes through an allow-list sanitizer, the only extra
+/// attribute kept is the `language-*` class of code blocks so they can be highlighted.
+pub fn render(markdown: &str) -> String {
+    let parser = Parser::new_ext(
+        markdown,
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH,
+    );
+    let mut unsafe_html = String::with_capacity(markdown.len() * 3 / 2);
+    html::push_html(&mut unsafe_html, parser);
+
+    SANITIZER
+        .get_or_init(|| {
+            let mut builder = ammonia::Builder::default();
+            builder
+                .add_tag_attributes("code", &["class"])
+                .attribute_filter(|element, attribute, value| {
+                    if element == "code" && attribute == "class" {
+                        value
+                            .split_ascii_whitespace()
+                            .find(|class| class.starts_with("language-"))
+                            .map(Into::into)
+                    } else {
+                        Some(value.into())
+                    }
+                });
+            builder
+        })
+        .clean(&unsafe_html)
+        .to_string()
+}
+
+/// Cleans a search snippet from the raw body, only the `<mark>` around the matches survives.
+pub fn sanitize_snippet(snippet: &str) -> String {
+    SNIPPET_SANITIZER
+        .get_or_init(|| {
+            let mut builder = ammonia::Builder::empty();
+            builder.add_tags(&["mark"]);
+            builder
+        })
+        .clean(snippet)
+        .to_string()
+}
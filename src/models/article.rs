@@ -1,150 +1,485 @@
 use super::UserPreview;
+#[cfg(feature = "ssr")]
+use super::{Cursor, Page};
 use serde::{Deserialize, Serialize};
 
+/// Only published articles are listed, the others are just visible for their author.
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum ArticleStatus {
+    Draft,
+    #[default]
+    Published,
+    /// Published by the scheduler once its date comes.
+    Scheduled,
+}
+
+/// Edits before the publication don't count, nobody else saw the article by then.
+#[cfg(feature = "ssr")]
+fn edited_at(
+    published_at: sqlx::types::chrono::DateTime<sqlx::types::chrono::Utc>,
+    updated_at: sqlx::types::chrono::DateTime<sqlx::types::chrono::Utc>,
+) -> Option<String> {
+    (updated_at > published_at).then(|| updated_at.format(super::DATE_FORMAT).to_string())
+}
+
+impl ArticleStatus {
+    #[cfg(feature = "ssr")]
+    fn from_db(status: &str) -> Self {
+        match status {
+            "draft" => Self::Draft,
+            "scheduled" => Self::Scheduled,
+            _ => Self::Published,
+        }
+    }
+
+    /// Name shown next to the articles that aren't public yet.
+    pub fn label(self) -> Option<&'static str> {
+        match self {
+            Self::Draft => Some("Draft"),
+            Self::Published => None,
+            Self::Scheduled => Some("Scheduled"),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Default)]
 pub struct Article {
     pub slug: String,
     pub title: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub body: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body_html: Option<String>,
+    /// Fragments of the body matching a search, as HTML with the matches in `<mark>`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
     pub description: String,
+    /// Publication date, or the creation one for drafts.
     pub created_at: String,
+    pub status: ArticleStatus,
+    /// Date of the last edit, only set when it came after `created_at`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+    /// Latest revision of the content, the editor shows what changed from it on conflicts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revision: Option<i64>,
+    /// Bumped on every update, the editor sends it back to detect concurrent edits.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<i64>,
     pub favorites_count: i64,
+    /// Comments left on the article, without the placeholders of the deleted ones.
+    pub comments_count: i64,
     pub tag_list: Vec<String>,
     pub author: UserPreview,
     pub fav: bool,
+    /// `created_at` as a Unix timestamp, for the feeds. The browser doesn't need it.
+    #[serde(skip)]
+    pub published_ts: i64,
+    /// Latest of the publication and the last edit as a Unix timestamp, for the feeds.
+    #[serde(skip)]
+    pub updated_ts: i64,
 }
 
 impl Article {
+    /// Published articles for the home feeds, newest first, paginated with cursors on
+    /// `(published_at, slug)` so deep pages are as fast as the first one.
     #[cfg(feature = "ssr")]
     pub async fn for_home_page(
-        page: i64,
         amount: i64,
         tag: String,
         my_feed: bool,
-    ) -> Result<Vec<Self>, sqlx::Error> {
-        let username = crate::auth::get_username();
-        sqlx::query!(
+        after: Option<Cursor>,
+        before: Option<Cursor>,
+    ) -> Result<Page<Self>, sqlx::Error> {
+        Self::for_feed(
+            crate::auth::get_username(),
+            amount,
+            tag,
+            my_feed,
+            after,
+            before,
+            None,
+        )
+        .await
+    }
+
+    /// The home feeds as seen by `username`, who doesn't need to be the logged user. With
+    /// `live_since`, only the articles that became public after it.
+    #[cfg(feature = "ssr")]
+    pub async fn for_feed(
+        username: Option<String>,
+        amount: i64,
+        tag: String,
+        my_feed: bool,
+        after: Option<Cursor>,
+        before: Option<Cursor>,
+        live_since: Option<sqlx::types::chrono::DateTime<sqlx::types::chrono::Utc>>,
+    ) -> Result<Page<Self>, sqlx::Error> {
+        let db = crate::database::get_db();
+        let cursor = before.as_ref().or(after.as_ref());
+        let cursor_date =
+            cursor.and_then(|x| sqlx::types::chrono::DateTime::from_timestamp_micros(x.date));
+        let cursor_slug = cursor.map(|x| x.key.clone());
+        let rows = sqlx::query!(
             "
-SELECT 
+WITH me AS (
+    SELECT id FROM Users WHERE username=$4
+), feed AS NOT MATERIALIZED (
+    SELECT a.id, a.slug, a.published_at as date
+    FROM Articles as a
+    WHERE
+        a.status='published'
+        AND
+        CASE WHEN $2!='' THEN a.id in (SELECT article_id FROM ArticleTags WHERE tag=$2)
+        ELSE 1=1
+        END
+        AND
+        CASE WHEN $3 THEN a.author_id in (SELECT influencer_id FROM Follows WHERE follower_id=(SELECT id FROM me))
+        ELSE 1=1
+        END
+        AND
+        ($8::timestamptz IS NULL OR a.live_at > $8)
+), page AS (
+    (SELECT id, date FROM feed
+    WHERE NOT $7 AND ($5::timestamptz IS NULL OR (date, slug) < ($5, $6))
+    ORDER BY date desc, slug desc
+    LIMIT $1)
+    UNION ALL
+    (SELECT id, date FROM feed
+    WHERE $7 AND (date, slug) > ($5, $6)
+    ORDER BY date, slug
+    LIMIT $1)
+)
+SELECT
     a.slug,
     a.title,
     a.description,
-    a.created_at,
-    (SELECT COUNT(*) FROM FavArticles WHERE article=a.slug) as favorites_count,
+    page.date as \"date!\",
+    a.updated_at,
+    (SELECT COUNT(*) FROM FavArticles WHERE article_id=a.id) as favorites_count,
+    (SELECT COUNT(*) FROM Comments WHERE article_id=a.id and deleted_at IS NULL) as comments_count,
     u.username, u.image,
-    EXISTS(SELECT 1 FROM FavArticles WHERE article=a.slug and username=$5) as fav,
-    EXISTS(SELECT 1 FROM Follows WHERE follower=$5 and influencer=u.username) as following,
-    (SELECT string_agg(tag, ' ') FROM ArticleTags WHERE article = a.slug) as tag_list
+    EXISTS(SELECT 1 FROM FavArticles WHERE article_id=a.id and user_id=(SELECT id FROM me)) as fav,
+    EXISTS(SELECT 1 FROM Follows WHERE follower_id=(SELECT id FROM me) and influencer_id=u.id) as following,
+    (SELECT string_agg(tag, ' ') FROM ArticleTags WHERE article_id = a.id) as tag_list
+FROM page
+    JOIN Articles as a ON a.id = page.id
+    JOIN Users as u ON a.author_id = u.id
+ORDER BY page.date desc, a.slug desc",
+            amount + 1,
+            tag,
+            my_feed,
+            username,
+            cursor_date,
+            cursor_slug,
+            before.is_some(),
+            live_since,
+        )
+        .map(|x| {
+            let cursor = Cursor {
+                date: x.date.timestamp_micros(),
+                key: x.slug.clone(),
+            };
+            let article = Self {
+                slug: x.slug,
+                title: x.title,
+                body: None, // no need
+                body_html: None,
+                snippet: None,
+                fav: x.fav.unwrap_or_default(),
+                description: x.description,
+                created_at: x.date.format(super::DATE_FORMAT).to_string(),
+                updated_at: edited_at(x.date, x.updated_at),
+                published_ts: x.date.timestamp(),
+                updated_ts: x.date.max(x.updated_at).timestamp(),
+                revision: None,
+                version: None,
+                status: ArticleStatus::Published,
+                favorites_count: x.favorites_count.unwrap_or_default(),
+                comments_count: x.comments_count.unwrap_or_default(),
+                author: UserPreview {
+                    username: x.username,
+                    image: x.image,
+                    following: x.following.unwrap_or_default(),
+                },
+                tag_list: x
+                    .tag_list
+                    .unwrap_or_default()
+                    .split(' ')
+                    .map(ToString::to_string)
+                    .collect::<Vec<String>>(),
+            };
+            (cursor, article)
+        })
+        .fetch_all(db)
+        .await?;
+
+        let total = sqlx::query_scalar!(
+            "
+SELECT COUNT(*)
 FROM Articles as a
-    JOIN Users as u ON a.author = u.username
 WHERE
-    CASE WHEN $3!='' THEN a.slug in (SELECT distinct article FROM ArticleTags WHERE tag=$3)
+    a.status='published'
+    AND
+    CASE WHEN $1!='' THEN a.id in (SELECT article_id FROM ArticleTags WHERE tag=$1)
     ELSE 1=1
     END
     AND
-    CASE WHEN $4 THEN u.username in (SELECT influencer FROM Follows WHERE follower=$5)
+    CASE WHEN $2 THEN a.author_id in (
+        SELECT f.influencer_id FROM Follows as f JOIN Users as u ON u.id = f.follower_id WHERE u.username=$3
+    )
     ELSE 1=1
     END
-ORDER BY a.created_at desc
-LIMIT $1 OFFSET $2",
-            amount,
-            page * amount,
+    AND
+    ($4::timestamptz IS NULL OR a.live_at > $4)",
             tag,
             my_feed,
             username,
+            live_since,
         )
-        .map(|x| Self {
-            slug: x.slug,
-            title: x.title,
-            body: None, // no need
-            fav: x.fav.unwrap_or_default(),
-            description: x.description,
-            created_at: x.created_at.format(super::DATE_FORMAT).to_string(),
-            favorites_count: x.favorites_count.unwrap_or_default(),
-            author: UserPreview {
-                username: x.username,
-                image: x.image,
-                following: x.following.unwrap_or_default(),
-            },
-            tag_list: x
-                .tag_list
-                .unwrap_or_default()
-                .split(' ')
-                .map(ToString::to_string)
-                .collect::<Vec<String>>(),
-        })
-        .fetch_all(crate::database::get_db())
-        .await
+        .fetch_one(db)
+        .await?
+        .unwrap_or_default();
+
+        Ok(Page::from_rows(
+            rows,
+            usize::try_from(amount).unwrap_or_default(),
+            after.as_ref(),
+            before.as_ref(),
+            total,
+        ))
     }
 
+    /// Articles written or favorited by the user, newest first, paginated like the home feeds.
+    /// The unpublished ones are only listed as `drafts` to their own author.
     #[cfg(feature = "ssr")]
     pub async fn for_user_profile(
         username: String,
         favourites: bool,
-    ) -> Result<Vec<Self>, sqlx::Error> {
+        drafts: bool,
+        amount: i64,
+        after: Option<Cursor>,
+        before: Option<Cursor>,
+    ) -> Result<Page<Self>, sqlx::Error> {
         let logged_user = crate::auth::get_username();
-        sqlx::query!(
+        let db = crate::database::get_db();
+        let cursor = before.as_ref().or(after.as_ref());
+        let cursor_date =
+            cursor.and_then(|x| sqlx::types::chrono::DateTime::from_timestamp_micros(x.date));
+        let cursor_slug = cursor.map(|x| x.key.clone());
+        let rows = sqlx::query!(
             "
-SELECT 
+WITH profile AS (
+    SELECT id FROM Users WHERE username=$1
+), me AS (
+    SELECT id FROM Users WHERE username=$2
+), list AS NOT MATERIALIZED (
+    SELECT a.id, a.slug, COALESCE(a.published_at, a.created_at) as date
+    FROM Articles as a
+    WHERE
+        CASE WHEN $3 THEN
+            a.status='published'
+            AND EXISTS(SELECT 1 FROM FavArticles as fa WHERE fa.article_id=a.id AND fa.user_id=(SELECT id FROM profile))
+        WHEN $8 THEN
+            a.status!='published' AND a.author_id = (SELECT id FROM profile) AND a.author_id = (SELECT id FROM me)
+        ELSE a.status='published' AND a.author_id = (SELECT id FROM profile)
+        END
+), page AS (
+    (SELECT id, date FROM list
+    WHERE NOT $7 AND ($5::timestamptz IS NULL OR (date, slug) < ($5, $6))
+    ORDER BY date desc, slug desc
+    LIMIT $4)
+    UNION ALL
+    (SELECT id, date FROM list
+    WHERE $7 AND (date, slug) > ($5, $6)
+    ORDER BY date, slug
+    LIMIT $4)
+)
+SELECT
     a.slug,
     a.title,
     a.description,
-    a.created_at,
+    a.status,
+    page.date as \"date!\",
+    a.updated_at,
     u.username,
     u.image,
-    (SELECT COUNT(*) FROM FavArticles WHERE article=a.slug) as favorites_count,
-    EXISTS(SELECT 1 FROM FavArticles WHERE article=a.slug and username=$2) as fav,
-    EXISTS(SELECT 1 FROM Follows WHERE follower=$2 and influencer=a.author) as following,
-    (SELECT string_agg(tag, ' ') FROM ArticleTags WHERE article = a.slug) as tag_list
-FROM Articles as a
-    JOIN Users as u ON u.username = a.author
-WHERE
-    CASE WHEN $3 THEN
-        EXISTS(SELECT fa.article, fa.username FROM FavArticles as fa WHERE fa.article=a.slug AND fa.username=$1)
-    ELSE a.author = $1
-    END",
+    (SELECT COUNT(*) FROM FavArticles WHERE article_id=a.id) as favorites_count,
+    (SELECT COUNT(*) FROM Comments WHERE article_id=a.id and deleted_at IS NULL) as comments_count,
+    EXISTS(SELECT 1 FROM FavArticles WHERE article_id=a.id and user_id=(SELECT id FROM me)) as fav,
+    EXISTS(SELECT 1 FROM Follows WHERE follower_id=(SELECT id FROM me) and influencer_id=a.author_id) as following,
+    (SELECT string_agg(tag, ' ') FROM ArticleTags WHERE article_id = a.id) as tag_list
+FROM page
+    JOIN Articles as a ON a.id = page.id
+    JOIN Users as u ON u.id = a.author_id
+ORDER BY page.date desc, a.slug desc",
             username,
             logged_user,
             favourites,
+            amount + 1,
+            cursor_date,
+            cursor_slug,
+            before.is_some(),
+            drafts,
         )
-        .map(|x| Self {
-            slug: x.slug,
-            title: x.title,
-            body: None, // no need
-            fav: x.fav.unwrap_or_default(),
-            description: x.description,
-            created_at: x.created_at.format(super::DATE_FORMAT).to_string(),
-            favorites_count: x.favorites_count.unwrap_or_default(),
-            tag_list: x
-                .tag_list
-                .map(|x| x.split(' ').map(ToString::to_string).collect::<Vec<_>>())
-                .unwrap_or_default(),
-            author: UserPreview {
-                username: x.username,
-                image: x.image,
-                following: x.following.unwrap_or_default(),
-            },
+        .map(|x| {
+            let cursor = Cursor {
+                date: x.date.timestamp_micros(),
+                key: x.slug.clone(),
+            };
+            let article = Self {
+                slug: x.slug,
+                title: x.title,
+                body: None, // no need
+                body_html: None,
+                snippet: None,
+                fav: x.fav.unwrap_or_default(),
+                description: x.description,
+                created_at: x.date.format(super::DATE_FORMAT).to_string(),
+                updated_at: edited_at(x.date, x.updated_at),
+                published_ts: x.date.timestamp(),
+                updated_ts: x.date.max(x.updated_at).timestamp(),
+                revision: None,
+                version: None,
+                status: ArticleStatus::from_db(&x.status),
+                favorites_count: x.favorites_count.unwrap_or_default(),
+                comments_count: x.comments_count.unwrap_or_default(),
+                tag_list: x
+                    .tag_list
+                    .map(|x| x.split(' ').map(ToString::to_string).collect::<Vec<_>>())
+                    .unwrap_or_default(),
+                author: UserPreview {
+                    username: x.username,
+                    image: x.image,
+                    following: x.following.unwrap_or_default(),
+                },
+            };
+            (cursor, article)
         })
+        .fetch_all(db)
+        .await?;
+
+        let total = sqlx::query_scalar!(
+            "
+SELECT COUNT(*)
+FROM Articles as a, (SELECT id, username FROM Users WHERE username=$1) as profile
+WHERE
+    CASE WHEN $2 THEN
+        a.status='published'
+        AND EXISTS(SELECT 1 FROM FavArticles as fa WHERE fa.article_id=a.id AND fa.user_id=profile.id)
+    WHEN $3 THEN
+        a.status!='published' AND a.author_id = profile.id AND profile.username = $4
+    ELSE a.status='published' AND a.author_id = profile.id
+    END",
+            username,
+            favourites,
+            drafts,
+            logged_user,
+        )
+        .fetch_one(db)
+        .await?
+        .unwrap_or_default();
+
+        Ok(Page::from_rows(
+            rows,
+            usize::try_from(amount).unwrap_or_default(),
+            after.as_ref(),
+            before.as_ref(),
+            total,
+        ))
+    }
+
+    /// Published articles matching the words of `query`, the most relevant first.
+    #[cfg(feature = "ssr")]
+    pub async fn search(query: String, amount: i64) -> Result<Page<Self>, sqlx::Error> {
+        let username = crate::auth::get_username();
+        let rows = sqlx::query!(
+            "
+SELECT
+    a.slug,
+    a.title,
+    a.description,
+    a.published_at as \"published_at!\",
+    a.updated_at,
+    (SELECT COUNT(*) FROM FavArticles WHERE article_id=a.id) as favorites_count,
+    (SELECT COUNT(*) FROM Comments WHERE article_id=a.id and deleted_at IS NULL) as comments_count,
+    u.username, u.image,
+    EXISTS(SELECT 1 FROM FavArticles as fa JOIN Users as me ON me.id = fa.user_id WHERE fa.article_id=a.id and me.username=$2) as fav,
+    EXISTS(SELECT 1 FROM Follows as f JOIN Users as me ON me.id = f.follower_id WHERE me.username=$2 and f.influencer_id=u.id) as following,
+    (SELECT string_agg(tag, ' ') FROM ArticleTags WHERE article_id = a.id) as tag_list,
+    ts_headline('english', a.body, query, 'StartSel=<mark>, StopSel=</mark>, MaxFragments=2, MaxWords=25, MinWords=10') as snippet,
+    COUNT(*) OVER () as total
+FROM Articles as a
+    JOIN Users as u ON a.author_id = u.id,
+    websearch_to_tsquery('english', $1) as query
+WHERE a.status='published' AND a.search @@ query
+ORDER BY ts_rank(a.search, query) desc, a.published_at desc, a.slug desc
+LIMIT $3",
+            query,
+            username,
+            amount,
+        )
         .fetch_all(crate::database::get_db())
-        .await
+        .await?;
+
+        let total = rows.first().and_then(|x| x.total).unwrap_or_default();
+        let items = rows
+            .into_iter()
+            .map(|x| Self {
+                slug: x.slug,
+                title: x.title,
+                body: None, // no need
+                body_html: None,
+                snippet: x.snippet.as_deref().map(crate::markdown::sanitize_snippet),
+                fav: x.fav.unwrap_or_default(),
+                description: x.description,
+                created_at: x.published_at.format(super::DATE_FORMAT).to_string(),
+                updated_at: edited_at(x.published_at, x.updated_at),
+                published_ts: x.published_at.timestamp(),
+                updated_ts: x.published_at.max(x.updated_at).timestamp(),
+                revision: None,
+                version: None,
+                status: ArticleStatus::Published,
+                favorites_count: x.favorites_count.unwrap_or_default(),
+                comments_count: x.comments_count.unwrap_or_default(),
+                author: UserPreview {
+                    username: x.username,
+                    image: x.image,
+                    following: x.following.unwrap_or_default(),
+                },
+                tag_list: x
+                    .tag_list
+                    .map(|x| x.split(' ').map(ToString::to_string).collect::<Vec<_>>())
+                    .unwrap_or_default(),
+            })
+            .collect::<Vec<_>>();
+        Ok(Page {
+            has_more: total > items.len() as i64,
+            items,
+            total,
+            ..Default::default()
+        })
     }
 
+    /// The article unless it's unpublished and the logged user isn't its author.
     #[cfg(feature = "ssr")]
     pub async fn for_article(slug: String) -> Result<Self, sqlx::Error> {
         let username = crate::auth::get_username();
         sqlx::query!(
             "
     SELECT
-        a.*,
-        (SELECT string_agg(tag, ' ') FROM ArticleTags WHERE article = a.slug) as tag_list,
-        (SELECT COUNT(*) FROM FavArticles WHERE article = a.slug) as fav_count,
-        u.*,
-        EXISTS(SELECT 1 FROM FavArticles WHERE article=a.slug and username=$2) as fav,
-        EXISTS(SELECT 1 FROM Follows WHERE follower=$2 and influencer=a.author) as following
+        a.slug, a.title, a.description, a.body, a.status, a.version,
+        COALESCE(a.published_at, a.created_at) as \"date!\", a.updated_at,
+        (SELECT max(id) FROM ArticleRevisions WHERE article_id = a.id) as revision,
+        (SELECT string_agg(tag, ' ') FROM ArticleTags WHERE article_id = a.id) as tag_list,
+        (SELECT COUNT(*) FROM FavArticles WHERE article_id = a.id) as fav_count,
+        (SELECT COUNT(*) FROM Comments WHERE article_id = a.id and deleted_at IS NULL) as comments_count,
+        u.username, u.image,
+        EXISTS(SELECT 1 FROM FavArticles as fa JOIN Users as me ON me.id = fa.user_id WHERE fa.article_id=a.id and me.username=$2) as fav,
+        EXISTS(SELECT 1 FROM Follows as f JOIN Users as me ON me.id = f.follower_id WHERE me.username=$2 and f.influencer_id=a.author_id) as following
     FROM Articles a
-        JOIN Users u ON a.author = u.username
-    WHERE slug = $1
+        JOIN Users u ON a.author_id = u.id
+    WHERE slug = $1 AND (a.status = 'published' OR u.username = $2)
     ",
             slug,
             username,
@@ -153,6 +488,8 @@ WHERE
             slug: x.slug,
             title: x.title,
             description: x.description,
+            body_html: Some(crate::markdown::render(&x.body)),
+            snippet: None,
             body: Some(x.body),
             tag_list: x
                 .tag_list
@@ -161,7 +498,14 @@ WHERE
                 .map(str::to_string)
                 .collect::<Vec<_>>(),
             favorites_count: x.fav_count.unwrap_or_default(),
-            created_at: x.created_at.format(super::DATE_FORMAT).to_string(),
+            comments_count: x.comments_count.unwrap_or_default(),
+            created_at: x.date.format(super::DATE_FORMAT).to_string(),
+            updated_at: edited_at(x.date, x.updated_at),
+            published_ts: x.date.timestamp(),
+            updated_ts: x.date.max(x.updated_at).timestamp(),
+            revision: x.revision,
+            version: Some(x.version),
+            status: ArticleStatus::from_db(&x.status),
             fav: x.fav.unwrap_or_default(),
             author: UserPreview {
                 username: x.username,
@@ -173,13 +517,32 @@ WHERE
         .await
     }
 
+    /// Current slug of an article that was renamed from `slug`.
+    #[cfg(feature = "ssr")]
+    pub async fn resolve_alias(slug: String) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar!("SELECT a.slug FROM SlugAliases as sa JOIN Articles as a ON a.id = sa.article_id WHERE sa.slug=$1", slug)
+            .fetch_optional(crate::database::get_db())
+            .await
+    }
+
+    /// Publishes the scheduled articles whose date already came, returns how many there were.
+    #[cfg(feature = "ssr")]
+    pub async fn publish_scheduled() -> Result<u64, sqlx::Error> {
+        sqlx::query!(
+            "UPDATE Articles SET status='published', live_at=NOW(), version=version+1 WHERE status='scheduled' and published_at <= NOW()"
+        )
+        .execute(crate::database::get_db())
+        .await
+        .map(|x| x.rows_affected())
+    }
+
     #[cfg(feature = "ssr")]
     pub async fn delete(
         slug: String,
         author: String,
     ) -> Result<sqlx::postgres::PgQueryResult, sqlx::Error> {
         sqlx::query!(
-            "DELETE FROM Articles WHERE slug=$1 and author=$2",
+            "DELETE FROM Articles WHERE slug=$1 and author_id=(SELECT id FROM Users WHERE username=$2)",
             slug,
             author
         )
@@ -1,59 +1,272 @@
-#[derive(Debug, serde::Deserialize, serde::Serialize, Clone)]
+#[cfg(feature = "ssr")]
+use super::{Cursor, Page};
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone, Default)]
 pub struct Comment {
     pub id: i32,
     pub article: String,
     pub username: String,
     pub body: String,
     pub created_at: String,
+    /// Date of the last edit of the body, if any.
+    pub edited_at: Option<String>,
     pub user_image: Option<String>,
+    pub parent_id: Option<i32>,
+    /// Placeholder of a deleted comment, kept because it has replies.
+    pub deleted: bool,
+    pub replies: Vec<Comment>,
 }
 
 impl Comment {
+    /// Replies nested deeper than this are shown next to their parent instead of under it.
+    pub const MAX_DEPTH: usize = 4;
+
+    /// Only published articles can be commented, and replies must go to a comment of the same
+    /// article that isn't deleted. The author of the article is notified.
     #[cfg(feature = "ssr")]
     pub async fn insert(
         article: String,
         username: String,
         body: String,
-    ) -> Result<sqlx::postgres::PgQueryResult, sqlx::Error> {
-        sqlx::query!(
-            "INSERT INTO Comments(article, username, body) VALUES ($1, $2, $3)",
+        parent_id: Option<i32>,
+    ) -> Result<i32, sqlx::Error> {
+        let mut transaction = crate::database::get_db().begin().await?;
+        let id = sqlx::query_scalar!(
+            "
+INSERT INTO Comments(article_id, user_id, body, parent_id)
+SELECT a.id, u.id, $3, $4 FROM Articles as a, Users as u
+WHERE a.slug=$1 AND a.status='published' AND u.username=$2 AND (
+    $4::int IS NULL
+    OR EXISTS(SELECT 1 FROM Comments WHERE id=$4 AND article_id=a.id AND deleted_at IS NULL)
+)
+RETURNING id",
             article,
             username,
-            body
+            body,
+            parent_id,
         )
-        .execute(crate::database::get_db())
-        .await
+        .fetch_one(transaction.as_mut())
+        .await?;
+        super::Notification::commented(transaction.as_mut(), id).await?;
+        transaction.commit().await?;
+        Ok(id)
     }
 
+    /// A page of the threads of the article, the comments without parent along with all
+    /// their replies. The threads go `newest` or oldest first, the replies always oldest first.
+    /// The total counts every comment but the placeholders of the deleted ones.
     #[cfg(feature = "ssr")]
-    pub async fn get_all(article: String) -> Result<Vec<Self>, sqlx::Error> {
-        sqlx::query!(
+    pub async fn get_page(
+        article: String,
+        newest: bool,
+        amount: i64,
+        after: Option<Cursor>,
+    ) -> Result<Page<Self>, sqlx::Error> {
+        let db = crate::database::get_db();
+        let cursor_date = after
+            .as_ref()
+            .and_then(|x| sqlx::types::chrono::DateTime::from_timestamp_micros(x.date));
+        let cursor_id = after.as_ref().and_then(|x| x.key.parse::<i32>().ok());
+        let rows = sqlx::query!(
+            "
+WITH threads AS NOT MATERIALIZED (
+    SELECT c.id, c.created_at
+    FROM Comments as c
+        JOIN Articles as a ON a.id = c.article_id
+    WHERE a.slug=$1 and c.parent_id IS NULL
+)
+(SELECT id as \"id!\", created_at as \"created_at!\" FROM threads
+WHERE $3 AND ($4::timestamptz IS NULL OR (created_at, id) < ($4, $5))
+ORDER BY created_at desc, id desc
+LIMIT $2)
+UNION ALL
+(SELECT id, created_at FROM threads
+WHERE NOT $3 AND ($4::timestamptz IS NULL OR (created_at, id) > ($4, $5))
+ORDER BY created_at, id
+LIMIT $2)",
+            article,
+            amount + 1,
+            newest,
+            cursor_date,
+            cursor_id,
+        )
+        .map(|x| {
+            let cursor = Cursor {
+                date: x.created_at.timestamp_micros(),
+                key: x.id.to_string(),
+            };
+            (cursor, x.id)
+        })
+        .fetch_all(db)
+        .await?;
+
+        let total = sqlx::query_scalar!(
             "
-        SELECT c.*, u.image FROM Comments as c
-            JOIN Users as u ON u.username=c.username
-        WHERE c.article=$1
-        ORDER BY c.created_at",
+SELECT COUNT(*)
+FROM Comments as c
+    JOIN Articles as a ON a.id = c.article_id
+WHERE a.slug=$1 and c.deleted_at IS NULL",
             article
         )
+        .fetch_one(db)
+        .await?
+        .unwrap_or_default();
+
+        let page = Page::from_rows(
+            rows,
+            usize::try_from(amount).unwrap_or_default(),
+            after.as_ref(),
+            None,
+            total,
+        );
+        let comments = sqlx::query!(
+            "
+WITH RECURSIVE thread AS (
+    SELECT id FROM Comments WHERE id = ANY($1)
+    UNION ALL
+    SELECT c.id FROM Comments as c JOIN thread ON c.parent_id = thread.id
+)
+SELECT
+    c.id, c.body, c.created_at, c.edited_at, a.slug as article, c.parent_id,
+    c.deleted_at IS NOT NULL as \"deleted!\",
+    COALESCE(u.username, '[deleted]') as \"username!\", u.image
+FROM thread
+    JOIN Comments as c ON c.id = thread.id
+    JOIN Articles as a ON a.id = c.article_id
+    LEFT JOIN Users as u ON u.id = c.user_id
+ORDER BY c.created_at, c.id",
+            &page.items,
+        )
         .map(|x| Self {
             id: x.id,
             article: x.article,
             username: x.username,
             body: x.body,
             created_at: x.created_at.format(super::DATE_FORMAT).to_string(),
+            edited_at: x
+                .edited_at
+                .map(|x| x.format(super::DATE_FORMAT).to_string()),
             user_image: x.image,
+            parent_id: x.parent_id,
+            deleted: x.deleted,
+            replies: Vec::new(),
         })
-        .fetch_all(crate::database::get_db())
+        .fetch_all(db)
+        .await?;
+
+        let mut threads = Self::into_tree(comments);
+        if newest {
+            threads.reverse();
+        }
+        Ok(Page {
+            items: threads,
+            next_cursor: page.next_cursor,
+            prev_cursor: page.prev_cursor,
+            has_more: page.has_more,
+            total: page.total,
+        })
+    }
+
+    /// Nests every comment under its parent, they come sorted by creation so the parents are
+    /// always seen before their replies.
+    #[cfg(feature = "ssr")]
+    fn into_tree(comments: Vec<Self>) -> Vec<Self> {
+        use std::collections::HashMap;
+
+        fn attach(
+            parent: Option<i32>,
+            replies: &mut HashMap<Option<i32>, Vec<Comment>>,
+        ) -> Vec<Comment> {
+            let mut comments = replies.remove(&parent).unwrap_or_default();
+            for comment in &mut comments {
+                comment.replies = attach(Some(comment.id), replies);
+            }
+            comments
+        }
+
+        // Where every comment ends up, with its depth
+        let mut placement = HashMap::<i32, (Option<i32>, usize)>::new();
+        let mut replies = HashMap::<Option<i32>, Vec<Self>>::new();
+        for comment in comments {
+            let parent = comment
+                .parent_id
+                .and_then(|x| placement.get(&x).map(|y| (x, *y)));
+            let (parent, depth) = match parent {
+                None => (None, 0),
+                // Too deep, it goes next to its parent
+                Some((_, (grandparent, depth))) if depth == Self::MAX_DEPTH => (grandparent, depth),
+                Some((parent, (_, depth))) => (Some(parent), depth + 1),
+            };
+            placement.insert(comment.id, (parent, depth));
+            replies.entry(parent).or_default().push(comment);
+        }
+        attach(None, &mut replies)
+    }
+
+    /// Only the author can edit the comment, saving the same body doesn't count as an edit.
+    ///
+    /// Returns `false` when the comment doesn't exist or belongs to somebody else.
+    #[cfg(feature = "ssr")]
+    pub async fn update(id: i32, user: String, body: String) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar!(
+            "
+UPDATE Comments SET
+    edited_at=CASE WHEN body=$3 THEN edited_at ELSE NOW() END,
+    body=$3
+WHERE id=$1 and user_id=(SELECT id FROM Users WHERE username=$2) and deleted_at IS NULL
+RETURNING id",
+            id,
+            user,
+            body,
+        )
+        .fetch_optional(crate::database::get_db())
         .await
+        .map(|x| x.is_some())
     }
 
+    /// Comments with replies become placeholders, the others are removed along with the
+    /// placeholders left without replies because of it.
     #[cfg(feature = "ssr")]
-    pub async fn delete(
-        id: i32,
-        user: String,
-    ) -> Result<sqlx::postgres::PgQueryResult, sqlx::Error> {
-        sqlx::query!("DELETE FROM Comments WHERE id=$1 and username=$2", id, user)
-            .execute(crate::database::get_db())
-            .await
+    pub async fn delete(id: i32, user: String) -> Result<(), sqlx::Error> {
+        let mut transaction = crate::database::get_db().begin().await?;
+        let placeholder = sqlx::query_scalar!(
+            "
+UPDATE Comments as c SET user_id=NULL, body='', deleted_at=NOW()
+WHERE id=$1 and user_id=(SELECT id FROM Users WHERE username=$2)
+    and EXISTS(SELECT 1 FROM Comments WHERE parent_id=c.id)
+RETURNING id",
+            id,
+            user
+        )
+        .fetch_optional(transaction.as_mut())
+        .await?;
+        if placeholder.is_some() {
+            // The placeholder stays for the replies, not for the notification
+            sqlx::query!("DELETE FROM Notifications WHERE comment_id=$1", id)
+                .execute(transaction.as_mut())
+                .await?;
+        } else {
+            let mut parent = sqlx::query_scalar!(
+                "DELETE FROM Comments WHERE id=$1 and user_id=(SELECT id FROM Users WHERE username=$2) RETURNING parent_id",
+                id,
+                user
+            )
+            .fetch_optional(transaction.as_mut())
+            .await?
+            .flatten();
+            while let Some(id) = parent {
+                parent = sqlx::query_scalar!(
+                    "
+DELETE FROM Comments as c
+WHERE id=$1 and deleted_at IS NOT NULL and NOT EXISTS(SELECT 1 FROM Comments WHERE parent_id=c.id)
+RETURNING parent_id",
+                    id
+                )
+                .fetch_optional(transaction.as_mut())
+                .await?
+                .flatten();
+            }
+        }
+        transaction.commit().await
     }
 }
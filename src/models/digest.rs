@@ -0,0 +1,73 @@
+use super::Article;
+
+/// Emails with the new articles of the authors the users follow, as often as they chose.
+pub struct Digest;
+
+impl Digest {
+    /// Articles listed at most in a digest, the rest are left for the feed.
+    pub const ARTICLES: i64 = 10;
+
+    /// Sends the digests that are due, returning how many emails were queued. Users without
+    /// new articles are skipped until their next digest.
+    ///
+    /// Every user is claimed in their own transaction, so several servers can run it at once.
+    #[tracing::instrument]
+    pub async fn send_due(host: &str) -> Result<usize, sqlx::Error> {
+        let mut sent = 0;
+        loop {
+            let mut transaction = crate::database::get_db().begin().await?;
+            let Some(user) = sqlx::query!(
+                "
+WITH due AS (
+    SELECT id, digest_sent_at FROM Users
+    WHERE digest_frequency != 'never' and email_verified_at IS NOT NULL
+        and digest_sent_at <= NOW() - CASE digest_frequency
+            WHEN 'daily' THEN interval '1 day'
+            ELSE interval '7 days'
+        END
+    ORDER BY digest_sent_at
+    LIMIT 1
+    FOR UPDATE SKIP LOCKED
+)
+UPDATE Users SET digest_sent_at=NOW()
+FROM due WHERE Users.id = due.id
+RETURNING Users.id, Users.username, Users.email, due.digest_sent_at as \"since!\""
+            )
+            .fetch_optional(transaction.as_mut())
+            .await?
+            else {
+                break;
+            };
+            // The newest ones that went live since the previous digest, scheduled articles count
+            // from when they were actually published
+            let page = Article::for_feed(
+                Some(user.username.clone()),
+                Self::ARTICLES,
+                String::new(),
+                true,
+                None,
+                None,
+                Some(user.since),
+            )
+            .await?;
+            if !page.items.is_empty() {
+                crate::mailer::Email::digest(
+                    user.email,
+                    &user.username,
+                    &page.items,
+                    page.has_more,
+                    host,
+                    &crate::auth::DigestUnsubscribe::link(host, user.id),
+                )
+                .enqueue(transaction.as_mut())
+                .await?;
+                sent += 1;
+            }
+            transaction.commit().await?;
+        }
+        if sent > 0 {
+            crate::mailer::wake_worker();
+        }
+        Ok(sent)
+    }
+}
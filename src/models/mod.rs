@@ -1,11 +1,21 @@
 mod user;
 pub use user::{User, UserPreview};
 mod pagination;
-pub use pagination::Pagination;
+#[cfg(feature = "ssr")]
+pub use pagination::Cursor;
+pub use pagination::{Page, Pagination};
 mod article;
-pub use article::Article;
+pub use article::{Article, ArticleStatus};
 mod comment;
 pub use comment::Comment;
+#[cfg(feature = "ssr")]
+mod digest;
+#[cfg(feature = "ssr")]
+pub use digest::Digest;
+mod notification;
+pub use notification::{DigestFrequency, Notification, NotificationKind, NotificationPreferences};
+mod revision;
+pub use revision::{DiffLine, Revision};
 
 #[cfg(feature = "ssr")]
 const DATE_FORMAT: &str = "%d/%m/%Y %H:%M";
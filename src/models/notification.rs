@@ -0,0 +1,281 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NotificationKind {
+    Follow,
+    Favorite,
+    Comment,
+}
+
+impl NotificationKind {
+    #[cfg(feature = "ssr")]
+    fn from_db(kind: &str) -> Self {
+        match kind {
+            "follow" => Self::Follow,
+            "favorite" => Self::Favorite,
+            _ => Self::Comment,
+        }
+    }
+}
+
+/// Something another user did with the profile or the articles of the notified one.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Notification {
+    pub id: i64,
+    pub kind: NotificationKind,
+    pub actor: String,
+    pub actor_image: Option<String>,
+    /// Slug of the article favorited or commented.
+    pub article: Option<String>,
+    pub article_title: Option<String>,
+    pub created_at: String,
+    pub read: bool,
+}
+
+/// How often the user gets an email with the new articles of the authors they follow.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum DigestFrequency {
+    #[default]
+    Never,
+    Daily,
+    Weekly,
+}
+
+impl DigestFrequency {
+    pub const ALL: [Self; 3] = [Self::Never, Self::Daily, Self::Weekly];
+
+    /// Value stored in the database and sent by the settings form.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Never => "never",
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+        }
+    }
+
+    pub fn parse(frequency: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|x| x.as_str() == frequency)
+    }
+}
+
+/// Kinds of notifications the user wants, all of them by default. The digest is opt-in.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct NotificationPreferences {
+    pub follows: bool,
+    pub favorites: bool,
+    pub comments: bool,
+    pub digest: DigestFrequency,
+}
+
+impl Notification {
+    /// Notifications shown at most, the newest first.
+    pub const LIMIT: i64 = 50;
+
+    /// Nobody is notified of their own actions, nor of the kinds they turned off.
+    #[cfg(feature = "ssr")]
+    pub async fn followed(
+        executor: impl sqlx::PgExecutor<'_>,
+        follower: &str,
+        influencer: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "
+INSERT INTO Notifications(user_id, actor_id, kind)
+SELECT i.id, f.id, 'follow' FROM Users as f, Users as i
+WHERE f.username=$1 and i.username=$2 and f.id != i.id and i.notify_follows",
+            follower,
+            influencer,
+        )
+        .execute(executor)
+        .await
+        .map(|_| ())
+    }
+
+    #[cfg(feature = "ssr")]
+    pub async fn favorited(
+        executor: impl sqlx::PgExecutor<'_>,
+        username: &str,
+        slug: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "
+INSERT INTO Notifications(user_id, actor_id, kind, article_id)
+SELECT author.id, u.id, 'favorite', a.id
+FROM Articles as a
+    JOIN Users as author ON author.id = a.author_id
+    JOIN Users as u ON u.username = $1
+WHERE a.slug=$2 and u.id != author.id and author.notify_favorites",
+            username,
+            slug,
+        )
+        .execute(executor)
+        .await
+        .map(|_| ())
+    }
+
+    #[cfg(feature = "ssr")]
+    pub async fn commented(
+        executor: impl sqlx::PgExecutor<'_>,
+        comment_id: i32,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "
+INSERT INTO Notifications(user_id, actor_id, kind, article_id, comment_id)
+SELECT author.id, c.user_id, 'comment', a.id, c.id
+FROM Comments as c
+    JOIN Articles as a ON a.id = c.article_id
+    JOIN Users as author ON author.id = a.author_id
+WHERE c.id=$1 and c.user_id != author.id and author.notify_comments",
+            comment_id,
+        )
+        .execute(executor)
+        .await
+        .map(|_| ())
+    }
+
+    /// Unfollowing takes back the notification if it wasn't read yet, so following and
+    /// unfollowing again doesn't flood the other user.
+    #[cfg(feature = "ssr")]
+    pub async fn unfollowed(
+        executor: impl sqlx::PgExecutor<'_>,
+        follower: &str,
+        influencer: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "
+DELETE FROM Notifications
+WHERE kind='follow' and read_at IS NULL
+    and actor_id=(SELECT id FROM Users WHERE username=$1)
+    and user_id=(SELECT id FROM Users WHERE username=$2)",
+            follower,
+            influencer,
+        )
+        .execute(executor)
+        .await
+        .map(|_| ())
+    }
+
+    /// Like [`Self::unfollowed`], for the favorites.
+    #[cfg(feature = "ssr")]
+    pub async fn unfavorited(
+        executor: impl sqlx::PgExecutor<'_>,
+        username: &str,
+        slug: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "
+DELETE FROM Notifications
+WHERE kind='favorite' and read_at IS NULL
+    and actor_id=(SELECT id FROM Users WHERE username=$1)
+    and article_id=(SELECT id FROM Articles WHERE slug=$2)",
+            username,
+            slug,
+        )
+        .execute(executor)
+        .await
+        .map(|_| ())
+    }
+
+    /// The latest notifications of the user, the newest first.
+    #[cfg(feature = "ssr")]
+    pub async fn get_all(username: String) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query!(
+            "
+SELECT
+    n.id, n.kind, n.created_at, n.read_at IS NOT NULL as \"read!\",
+    actor.username as actor, actor.image as actor_image,
+    a.slug as \"article?\", a.title as \"article_title?\"
+FROM Notifications as n
+    JOIN Users as u ON u.id = n.user_id
+    JOIN Users as actor ON actor.id = n.actor_id
+    LEFT JOIN Articles as a ON a.id = n.article_id
+WHERE u.username=$1
+ORDER BY n.id desc
+LIMIT $2",
+            username,
+            Self::LIMIT,
+        )
+        .map(|x| Self {
+            id: x.id,
+            kind: NotificationKind::from_db(&x.kind),
+            actor: x.actor,
+            actor_image: x.actor_image,
+            article: x.article,
+            article_title: x.article_title,
+            created_at: x.created_at.format(super::DATE_FORMAT).to_string(),
+            read: x.read,
+        })
+        .fetch_all(crate::database::get_db())
+        .await
+    }
+
+    #[cfg(feature = "ssr")]
+    pub async fn unread_count(username: String) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            "
+SELECT COUNT(*) as \"count!\"
+FROM Notifications as n
+    JOIN Users as u ON u.id = n.user_id
+WHERE u.username=$1 and n.read_at IS NULL",
+            username,
+        )
+        .fetch_one(crate::database::get_db())
+        .await
+    }
+
+    /// Marks the notification as read, or all of them without `id`.
+    #[cfg(feature = "ssr")]
+    pub async fn mark_read(username: String, id: Option<i64>) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "
+UPDATE Notifications SET read_at=NOW()
+WHERE user_id=(SELECT id FROM Users WHERE username=$1) and read_at IS NULL
+    and ($2::bigint IS NULL OR id=$2)",
+            username,
+            id,
+        )
+        .execute(crate::database::get_db())
+        .await
+        .map(|_| ())
+    }
+}
+
+impl NotificationPreferences {
+    #[cfg(feature = "ssr")]
+    pub async fn get(username: String) -> Result<Self, sqlx::Error> {
+        sqlx::query!(
+            "
+SELECT notify_follows, notify_favorites, notify_comments, digest_frequency
+FROM Users WHERE username=$1",
+            username,
+        )
+        .map(|x| Self {
+            follows: x.notify_follows,
+            favorites: x.notify_favorites,
+            comments: x.notify_comments,
+            digest: DigestFrequency::parse(&x.digest_frequency).unwrap_or_default(),
+        })
+        .fetch_one(crate::database::get_db())
+        .await
+    }
+
+    /// Subscribing to the digest starts it from now, not from the previous subscription.
+    #[cfg(feature = "ssr")]
+    pub async fn update(self, username: String) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "
+UPDATE Users SET
+    notify_follows=$2, notify_favorites=$3, notify_comments=$4, digest_frequency=$5,
+    digest_sent_at=CASE WHEN digest_frequency='never' THEN NOW() ELSE digest_sent_at END
+WHERE username=$1",
+            username,
+            self.follows,
+            self.favorites,
+            self.comments,
+            self.digest.as_str(),
+        )
+        .execute(crate::database::get_db())
+        .await
+        .map(|_| ())
+    }
+}
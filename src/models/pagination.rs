@@ -1,12 +1,14 @@
 use leptos::prelude::*;
 use leptos_router::params::Params;
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Params, PartialEq, Clone)]
 pub struct Pagination {
     tag: Option<String>,
     my_feed: Option<bool>,
-    page: Option<u32>,
     amount: Option<u32>,
+    after: Option<String>,
+    before: Option<String>,
 }
 
 impl Pagination {
@@ -19,13 +21,17 @@ impl Pagination {
         self.my_feed.unwrap_or_default()
     }
     #[inline]
-    pub fn get_page(&self) -> u32 {
-        self.page.unwrap_or_default()
-    }
-    #[inline]
     pub fn get_amount(&self) -> u32 {
         self.amount.unwrap_or(10)
     }
+    #[inline]
+    pub fn get_after(&self) -> Option<String> {
+        self.after.clone()
+    }
+    #[inline]
+    pub fn get_before(&self) -> Option<String> {
+        self.before.clone()
+    }
 
     #[inline]
     pub fn set_tag<T: ToString + ?Sized>(mut self, tag: &T) -> Self {
@@ -47,21 +53,36 @@ impl Pagination {
 
     #[inline]
     pub fn reset_page(mut self) -> Self {
-        self.page = Some(0);
+        self.after = None;
+        self.before = None;
         self
     }
 
     #[inline]
-    pub fn next_page(mut self) -> Self {
-        self.page = Some(self.page.unwrap_or_default().saturating_add(1));
+    pub fn next_page(mut self, cursor: &str) -> Self {
+        self.after = Some(cursor.to_string());
+        self.before = None;
         self
     }
 
     #[inline]
-    pub fn previous_page(mut self) -> Self {
-        self.page = Some(self.page.unwrap_or_default().saturating_sub(1));
+    pub fn previous_page(mut self, cursor: &str) -> Self {
+        self.after = None;
+        self.before = Some(cursor.to_string());
         self
     }
+
+    /// Query string of the page alone, for lists that don't have feeds nor tags.
+    pub fn page_query(&self) -> String {
+        let mut query = format!("amount={}", self.get_amount());
+        if let Some(after) = self.after.as_ref() {
+            query.push_str(&format!("&after={after}"));
+        }
+        if let Some(before) = self.before.as_ref() {
+            query.push_str(&format!("&before={before}"));
+        }
+        query
+    }
 }
 
 impl Default for Pagination {
@@ -69,8 +90,9 @@ impl Default for Pagination {
         Self {
             tag: Some(String::new()),
             my_feed: Some(false),
-            page: Some(0),
             amount: Some(10),
+            after: None,
+            before: None,
         }
     }
 }
@@ -79,11 +101,92 @@ impl std::fmt::Display for Pagination {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "/?tag={}&my_feed={}&page={}&amount={}",
+            "/?tag={}&my_feed={}&{}",
             self.get_tag(),
             self.get_my_feed(),
-            self.get_page(),
-            self.get_amount(),
+            self.page_query(),
         )
     }
 }
+
+/// Position of an item in a list sorted by `(date, key)`, it goes in the query string as
+/// `<date in microseconds>.<key>`. Articles are keyed by slug and sorted by publication, drafts
+/// use their creation. Comments are keyed by id and sorted by creation.
+#[cfg(feature = "ssr")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cursor {
+    pub date: i64,
+    pub key: String,
+}
+
+#[cfg(feature = "ssr")]
+impl std::fmt::Display for Cursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.date, self.key)
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl std::str::FromStr for Cursor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (date, key) = s.split_once('.').ok_or("cursor without key")?;
+        Ok(Self {
+            date: date
+                .parse()
+                .map_err(|_| format!("invalid cursor date {date}"))?,
+            key: key.to_string(),
+        })
+    }
+}
+
+/// A page of a list paginated with cursors.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Set when `has_more`, to ask for the items following this page.
+    pub next_cursor: Option<String>,
+    /// Set unless this is the first page, to ask for the items preceding this page.
+    pub prev_cursor: Option<String>,
+    pub has_more: bool,
+    /// Items in the whole list, not only in this page.
+    pub total: i64,
+}
+
+#[cfg(feature = "ssr")]
+impl<T> Page<T> {
+    /// Builds the page from up to `amount + 1` rows sorted in the list order, the extra row
+    /// only tells whether there are more items in the direction we are moving.
+    pub fn from_rows(
+        mut rows: Vec<(Cursor, T)>,
+        amount: usize,
+        after: Option<&Cursor>,
+        before: Option<&Cursor>,
+        total: i64,
+    ) -> Self {
+        let extra = rows.len() > amount;
+        if extra {
+            if before.is_some() {
+                rows.remove(0);
+            } else {
+                rows.pop();
+            }
+        }
+        let first = rows.first().map(|(cursor, _)| cursor.to_string());
+        let last = rows.last().map(|(cursor, _)| cursor.to_string());
+        let (next_cursor, prev_cursor) = if before.is_some() {
+            // We come from the following page, so it's there
+            (last, first.filter(|_| extra))
+        } else {
+            (last.filter(|_| extra), first.filter(|_| after.is_some()))
+        };
+        Self {
+            items: rows.into_iter().map(|(_, item)| item).collect(),
+            has_more: next_cursor.is_some(),
+            next_cursor,
+            prev_cursor,
+            total,
+        }
+    }
+}
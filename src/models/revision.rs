@@ -0,0 +1,84 @@
+/// Version of the title, description and body of an article, one is stored on every edit.
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone)]
+pub struct Revision {
+    pub id: i64,
+    pub title: String,
+    pub created_at: String,
+}
+
+/// Line of the diff between two revisions.
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone, PartialEq, Eq)]
+pub enum DiffLine {
+    Same(String),
+    Added(String),
+    Removed(String),
+}
+
+impl Revision {
+    /// Stores the content of the article, unless it's the same as in its latest revision.
+    #[cfg(feature = "ssr")]
+    pub async fn insert(
+        executor: impl sqlx::PgExecutor<'_>,
+        article_id: i64,
+        title: &str,
+        description: &str,
+        body: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "
+INSERT INTO ArticleRevisions(article_id, title, description, body)
+SELECT $1, $2, $3, $4
+WHERE NOT EXISTS(
+    SELECT 1 FROM (
+        SELECT title, description, body FROM ArticleRevisions WHERE article_id=$1 ORDER BY id desc LIMIT 1
+    ) as latest
+    WHERE (latest.title, latest.description, latest.body) = ($2, $3, $4)
+)",
+            article_id,
+            title,
+            description,
+            body,
+        )
+        .execute(executor)
+        .await
+        .map(|_| ())
+    }
+
+    /// Revisions of the article, the newest first.
+    #[cfg(feature = "ssr")]
+    pub async fn get_all(slug: String) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query!(
+            "
+SELECT r.id, r.title, r.created_at
+FROM ArticleRevisions as r
+    JOIN Articles as a ON a.id = r.article_id
+WHERE a.slug=$1
+ORDER BY r.id desc",
+            slug
+        )
+        .map(|x| Self {
+            id: x.id,
+            title: x.title,
+            created_at: x.created_at.format(super::DATE_FORMAT).to_string(),
+        })
+        .fetch_all(crate::database::get_db())
+        .await
+    }
+
+    /// Content of the revision as the text compared by the diffs, every field in its own lines.
+    #[cfg(feature = "ssr")]
+    pub async fn text(slug: String, id: i64) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query!(
+            "
+SELECT r.title, r.description, r.body
+FROM ArticleRevisions as r
+    JOIN Articles as a ON a.id = r.article_id
+WHERE a.slug=$1 and r.id=$2",
+            slug,
+            id
+        )
+        .map(|x| format!("# {}\n\n{}\n\n{}", x.title, x.description, x.body))
+        .fetch_optional(crate::database::get_db())
+        .await
+    }
+}
@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
 
+/// Shown instead of the username of the authors of comments whose account was deleted.
+const DELETED_USERNAME: &str = "[deleted]";
+
 #[derive(Serialize, Deserialize, Clone, Default)]
 pub struct UserPreview {
     pub username: String,
@@ -16,12 +19,16 @@ pub struct User {
     email: String,
     bio: Option<String>,
     image: Option<String>,
+    email_verified: bool,
 }
 
 #[cfg(feature = "ssr")]
 static EMAIL_REGEX: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
 
 impl User {
+    /// Days to wait between two username changes, so names can't be juggled around.
+    pub const RENAME_COOLDOWN_DAYS: i32 = 7;
+
     #[inline]
     pub fn username(&self) -> String {
         self.username.clone()
@@ -38,6 +45,10 @@ impl User {
     pub fn image(&self) -> Option<String> {
         self.image.clone()
     }
+    #[inline]
+    pub fn email_verified(&self) -> bool {
+        self.email_verified
+    }
 
     pub fn set_password(mut self, password: String) -> Result<Self, String> {
         if password.len() < 4 {
@@ -47,12 +58,25 @@ impl User {
         Ok(self)
     }
 
+    /// Usernames go in the URLs of the profiles and feeds, so they are restricted to letters,
+    /// digits, `_` and `-`. The placeholder of deleted users is never a valid one.
     pub fn set_username(mut self, username: String) -> Result<Self, String> {
         if username.len() < 4 {
             return Err(format!(
                 "Username {username} is too short, at least 4 characters"
             ));
         }
+        if username == DELETED_USERNAME {
+            return Err(format!("Username {username} is not available"));
+        }
+        if !username
+            .chars()
+            .all(|x| x.is_ascii_alphanumeric() || x == '_' || x == '-')
+        {
+            return Err(format!(
+                "Username {username} can only contain letters, digits, _ and -"
+            ));
+        }
         self.username = username;
         Ok(self)
     }
@@ -108,7 +132,7 @@ impl User {
     pub async fn get(username: String) -> Result<Self, sqlx::Error> {
         sqlx::query_as!(
             Self,
-            "SELECT username, email, bio, image, NULL as password FROM users WHERE username=$1",
+            "SELECT username, email, bio, image, NULL as password, email_verified_at IS NOT NULL as \"email_verified!\" FROM users WHERE username=$1",
             username
         )
         .fetch_one(crate::database::get_db())
@@ -119,7 +143,7 @@ impl User {
     pub async fn get_email(email: String) -> Result<Self, sqlx::Error> {
         sqlx::query_as!(
             Self,
-            "SELECT username, email, bio, image, NULL as password FROM users WHERE email=$1",
+            "SELECT username, email, bio, image, NULL as password, email_verified_at IS NOT NULL as \"email_verified!\" FROM users WHERE email=$1",
             email
         )
         .fetch_one(crate::database::get_db())
@@ -127,25 +151,151 @@ impl User {
     }
 
     #[cfg(feature = "ssr")]
-    pub async fn insert(&self) -> Result<sqlx::postgres::PgQueryResult, sqlx::Error> {
+    pub async fn insert(
+        &self,
+        executor: impl sqlx::PgExecutor<'_>,
+    ) -> Result<sqlx::postgres::PgQueryResult, sqlx::Error> {
         sqlx::query!(
             "INSERT INTO Users(username, email, password) VALUES ($1, $2, crypt($3, gen_salt('bf')))",
             self.username,
             self.email,
             self.password,
         )
-            .execute(crate::database::get_db())
+            .execute(executor)
             .await
     }
 
+    /// Gives the user a new username, keeping the current one as an alias so links to the
+    /// profile keep working. It runs inside the caller's transaction.
+    ///
+    /// Returns `false` without renaming when the user was renamed less than
+    /// [`Self::RENAME_COOLDOWN_DAYS`] ago.
+    #[cfg(feature = "ssr")]
+    pub async fn rename(
+        connection: &mut sqlx::PgConnection,
+        username: &str,
+        new_username: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let recently_renamed = sqlx::query_scalar!(
+            "
+SELECT EXISTS(
+    SELECT 1 FROM UsernameAliases as ua JOIN Users as u ON u.id = ua.user_id
+    WHERE u.username=$1 and ua.created_at > NOW() - make_interval(days => $2)
+)",
+            username,
+            Self::RENAME_COOLDOWN_DAYS,
+        )
+        .fetch_one(&mut *connection)
+        .await?
+        .unwrap_or_default();
+        if recently_renamed {
+            return Ok(false);
+        }
+        let id = sqlx::query_scalar!(
+            "UPDATE Users SET username=$2 WHERE username=$1 RETURNING id",
+            username,
+            new_username,
+        )
+        .fetch_one(&mut *connection)
+        .await?;
+        // The new name may be an old one, a user always wins over an alias
+        sqlx::query!(
+            "DELETE FROM UsernameAliases WHERE username=$1",
+            new_username
+        )
+        .execute(&mut *connection)
+        .await?;
+        sqlx::query!(
+            "
+INSERT INTO UsernameAliases(username, user_id) VALUES ($1, $2)
+ON CONFLICT (username) DO UPDATE SET user_id=EXCLUDED.user_id, created_at=NOW()",
+            username,
+            id,
+        )
+        .execute(connection)
+        .await?;
+        Ok(true)
+    }
+
+    /// Current username of a user that was renamed from `username`.
+    #[cfg(feature = "ssr")]
+    pub async fn resolve_alias(username: String) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar!(
+            "SELECT u.username FROM UsernameAliases as ua JOIN Users as u ON u.id = ua.user_id WHERE ua.username=$1",
+            username
+        )
+        .fetch_optional(crate::database::get_db())
+        .await
+    }
+
+    /// Deletes the account if the password matches, everything the user created goes away
+    /// with it except the comments when `anonymize_comments` is set, they stay without author.
+    ///
+    /// Returns `false` when the password is wrong.
+    #[cfg(feature = "ssr")]
+    pub async fn delete(
+        username: String,
+        password: String,
+        anonymize_comments: bool,
+    ) -> Result<bool, sqlx::Error> {
+        let mut transaction = crate::database::get_db().begin().await?;
+        let Some(id) = sqlx::query_scalar!(
+            "SELECT id FROM Users WHERE username=$1 and password=crypt($2, password) FOR UPDATE",
+            username,
+            password,
+        )
+        .fetch_optional(transaction.as_mut())
+        .await?
+        else {
+            return Ok(false);
+        };
+        if anonymize_comments {
+            sqlx::query!("UPDATE Comments SET user_id=NULL WHERE user_id=$1", id)
+                .execute(transaction.as_mut())
+                .await?;
+        } else {
+            // The replies of other users don't go away with the comments they answer
+            sqlx::query!(
+                "
+WITH RECURSIVE answered AS (
+    SELECT parent_id as id FROM Comments WHERE user_id IS DISTINCT FROM $1 and parent_id IS NOT NULL
+    UNION
+    SELECT c.parent_id FROM Comments as c JOIN answered ON answered.id = c.id WHERE c.parent_id IS NOT NULL
+)
+UPDATE Comments SET user_id=NULL, body='', deleted_at=NOW()
+WHERE user_id=$1 and id IN (SELECT id FROM answered)",
+                id
+            )
+            .execute(transaction.as_mut())
+            .await?;
+        }
+        // Pending emails would go on carrying the address and one-time links
+        sqlx::query!(
+            "DELETE FROM EmailOutbox WHERE recipient=(SELECT email FROM Users WHERE id=$1)",
+            id
+        )
+        .execute(transaction.as_mut())
+        .await?;
+        sqlx::query!("DELETE FROM Users WHERE id=$1", id)
+            .execute(transaction.as_mut())
+            .await?;
+        transaction.commit().await?;
+        Ok(true)
+    }
+
+    /// Changing the email makes it unverified again.
     #[cfg(feature = "ssr")]
-    pub async fn update(&self) -> Result<sqlx::postgres::PgQueryResult, sqlx::Error> {
+    pub async fn update(
+        &self,
+        executor: impl sqlx::PgExecutor<'_>,
+    ) -> Result<sqlx::postgres::PgQueryResult, sqlx::Error> {
         sqlx::query!(
             "
 UPDATE Users SET
     image=$2,
     bio=$3,
     email=$4,
+    email_verified_at=CASE WHEN email=$4 THEN email_verified_at END,
     password=CASE WHEN $5 THEN crypt($6, gen_salt('bf')) ELSE password END
 WHERE username=$1",
             self.username,
@@ -155,7 +305,7 @@ WHERE username=$1",
             self.password.is_some(),
             self.password,
         )
-        .execute(crate::database::get_db())
+        .execute(executor)
         .await
     }
 }
@@ -0,0 +1,357 @@
+use axum::{
+    Json,
+    extract::{Path, Query},
+    http::StatusCode,
+    response::{IntoResponse, Redirect, Response},
+};
+use serde::{Deserialize, Serialize};
+
+use super::{ApiError, ApiResult, AuthUser, profiles::Profile};
+
+const DEFAULT_LIMIT: i64 = 20;
+
+#[derive(Deserialize, Serialize)]
+pub struct ArticleBody<T> {
+    article: T,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArticleList {
+    articles: Vec<ArticleResponse>,
+    articles_count: i64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArticleResponse {
+    slug: String,
+    title: String,
+    description: String,
+    body: String,
+    tag_list: Vec<String>,
+    created_at: String,
+    updated_at: String,
+    favorited: bool,
+    favorites_count: i64,
+    author: Profile,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewArticle {
+    title: String,
+    description: String,
+    body: String,
+    #[serde(default)]
+    tag_list: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArticleChanges {
+    title: Option<String>,
+    description: Option<String>,
+    body: Option<String>,
+    tag_list: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct ListQuery {
+    tag: Option<String>,
+    author: Option<String>,
+    favorited: Option<String>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct TagList {
+    tags: Vec<String>,
+}
+
+#[derive(Default)]
+struct Filter {
+    slug: Option<String>,
+    query: ListQuery,
+    feed: bool,
+}
+
+async fn fetch_articles(
+    logged_user: Option<String>,
+    filter: Filter,
+) -> Result<ArticleList, sqlx::Error> {
+    let db = crate::database::get_db();
+    let Filter { slug, query, feed } = filter;
+    let articles = sqlx::query!(
+        "
+WITH me AS (
+    SELECT id FROM Users WHERE username=$1
+)
+SELECT
+    a.slug,
+    a.title,
+    a.description,
+    a.body,
+    a.created_at,
+    a.updated_at,
+    u.username,
+    u.bio,
+    u.image,
+    (SELECT COUNT(*) FROM FavArticles WHERE article_id=a.id) as favorites_count,
+    EXISTS(SELECT 1 FROM FavArticles WHERE article_id=a.id and user_id=(SELECT id FROM me)) as fav,
+    EXISTS(SELECT 1 FROM Follows WHERE follower_id=(SELECT id FROM me) and influencer_id=u.id) as following,
+    (SELECT array_agg(tag ORDER BY tag) FROM ArticleTags WHERE article_id = a.id) as tag_list
+FROM Articles as a
+    JOIN Users as u ON a.author_id = u.id
+WHERE
+    a.status = 'published'
+    AND ($2::text IS NULL OR a.slug = $2)
+    AND ($3::text IS NULL OR EXISTS(SELECT 1 FROM ArticleTags WHERE article_id=a.id and tag=$3))
+    AND ($4::text IS NULL OR u.username = $4)
+    AND ($5::text IS NULL OR EXISTS(
+        SELECT 1 FROM FavArticles as fa JOIN Users as fu ON fu.id = fa.user_id
+        WHERE fa.article_id=a.id and fu.username=$5
+    ))
+    AND (NOT $6 OR a.author_id IN (SELECT influencer_id FROM Follows WHERE follower_id=(SELECT id FROM me)))
+ORDER BY a.published_at desc
+LIMIT $7 OFFSET $8",
+        logged_user,
+        slug,
+        query.tag,
+        query.author,
+        query.favorited,
+        feed,
+        query.limit.unwrap_or(DEFAULT_LIMIT),
+        query.offset.unwrap_or_default(),
+    )
+    .map(|x| ArticleResponse {
+        slug: x.slug,
+        title: x.title,
+        description: x.description,
+        body: x.body,
+        tag_list: x.tag_list.unwrap_or_default(),
+        created_at: super::format_date(x.created_at),
+        updated_at: super::format_date(x.updated_at),
+        favorited: x.fav.unwrap_or_default(),
+        favorites_count: x.favorites_count.unwrap_or_default(),
+        author: Profile {
+            username: x.username,
+            bio: x.bio,
+            image: x.image,
+            following: x.following.unwrap_or_default(),
+        },
+    })
+    .fetch_all(db)
+    .await?;
+
+    let articles_count = sqlx::query_scalar!(
+        "
+SELECT COUNT(*)
+FROM Articles as a
+    JOIN Users as u ON a.author_id = u.id
+WHERE
+    a.status = 'published'
+    AND ($2::text IS NULL OR a.slug = $2)
+    AND ($3::text IS NULL OR EXISTS(SELECT 1 FROM ArticleTags WHERE article_id=a.id and tag=$3))
+    AND ($4::text IS NULL OR u.username = $4)
+    AND ($5::text IS NULL OR EXISTS(
+        SELECT 1 FROM FavArticles as fa JOIN Users as fu ON fu.id = fa.user_id
+        WHERE fa.article_id=a.id and fu.username=$5
+    ))
+    AND (NOT $6 OR a.author_id IN (
+        SELECT f.influencer_id FROM Follows as f JOIN Users as me ON me.id = f.follower_id WHERE me.username=$1
+    ))",
+        logged_user,
+        slug,
+        query.tag,
+        query.author,
+        query.favorited,
+        feed,
+    )
+    .fetch_one(db)
+    .await?
+    .unwrap_or_default();
+
+    Ok(ArticleList {
+        articles,
+        articles_count,
+    })
+}
+
+async fn fetch_article(
+    slug: String,
+    logged_user: Option<String>,
+) -> Result<ArticleResponse, ApiError> {
+    let filter = Filter {
+        slug: Some(slug),
+        ..Default::default()
+    };
+    fetch_articles(logged_user, filter)
+        .await?
+        .articles
+        .pop()
+        .ok_or(ApiError::NotFound)
+}
+
+#[tracing::instrument(skip(auth))]
+pub async fn list(
+    auth: Option<AuthUser>,
+    Query(query): Query<ListQuery>,
+) -> ApiResult<ArticleList> {
+    let filter = Filter {
+        query,
+        ..Default::default()
+    };
+    Ok(Json(
+        fetch_articles(AuthUser::username(auth.as_ref()), filter).await?,
+    ))
+}
+
+#[tracing::instrument(skip(auth))]
+pub async fn feed(auth: AuthUser, Query(query): Query<ListQuery>) -> ApiResult<ArticleList> {
+    let filter = Filter {
+        query: ListQuery {
+            limit: query.limit,
+            offset: query.offset,
+            ..Default::default()
+        },
+        feed: true,
+        ..Default::default()
+    };
+    Ok(Json(fetch_articles(Some(auth.username), filter).await?))
+}
+
+#[tracing::instrument(skip(auth))]
+pub async fn get_article(
+    auth: Option<AuthUser>,
+    Path(slug): Path<String>,
+) -> Result<Response, ApiError> {
+    match fetch_article(slug.clone(), AuthUser::username(auth.as_ref())).await {
+        Ok(article) => Ok(Json(ArticleBody { article }).into_response()),
+        // Renamed articles are still reachable through their old slugs
+        Err(ApiError::NotFound) => match crate::models::Article::resolve_alias(slug).await? {
+            Some(canonical) => {
+                Ok(Redirect::permanent(&format!("/api/articles/{canonical}")).into_response())
+            }
+            None => Err(ApiError::NotFound),
+        },
+        Err(x) => Err(x),
+    }
+}
+
+/// The API publishes right away, which needs a verified email like in the editor.
+async fn check_can_publish(username: &str) -> Result<(), ApiError> {
+    if crate::models::User::get(username.to_string())
+        .await?
+        .email_verified()
+    {
+        Ok(())
+    } else {
+        Err(ApiError::Validation(
+            "you need to verify your email before publishing".into(),
+        ))
+    }
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn create(
+    auth: AuthUser,
+    Json(ArticleBody { article }): Json<ArticleBody<NewArticle>>,
+) -> ApiResult<ArticleBody<ArticleResponse>> {
+    check_can_publish(&auth.username).await?;
+    let article = crate::routes::validate_article(
+        article.title,
+        article.description,
+        article.body,
+        &article.tag_list.join(" "),
+    )
+    .map_err(ApiError::Validation)?;
+    let slug = crate::routes::update_article(
+        auth.username.clone(),
+        String::new(),
+        article,
+        crate::routes::Publication::Now,
+        None,
+    )
+    .await?;
+
+    let article = fetch_article(slug, Some(auth.username)).await?;
+    Ok(Json(ArticleBody { article }))
+}
+
+#[tracing::instrument(skip(auth, changes))]
+pub async fn update(
+    auth: AuthUser,
+    Path(slug): Path<String>,
+    Json(ArticleBody { article: changes }): Json<ArticleBody<ArticleChanges>>,
+) -> ApiResult<ArticleBody<ArticleResponse>> {
+    let current = fetch_article(slug.clone(), Some(auth.username.clone())).await?;
+    if current.author.username != auth.username {
+        return Err(ApiError::Forbidden);
+    }
+    check_can_publish(&auth.username).await?;
+    let article = crate::routes::validate_article(
+        changes.title.unwrap_or(current.title),
+        changes.description.unwrap_or(current.description),
+        changes.body.unwrap_or(current.body),
+        &changes.tag_list.unwrap_or(current.tag_list).join(" "),
+    )
+    .map_err(ApiError::Validation)?;
+    let slug = crate::routes::update_article(
+        auth.username.clone(),
+        slug,
+        article,
+        crate::routes::Publication::Now,
+        None,
+    )
+    .await?;
+
+    let article = fetch_article(slug, Some(auth.username)).await?;
+    Ok(Json(ArticleBody { article }))
+}
+
+#[tracing::instrument(skip(auth))]
+pub async fn delete(auth: AuthUser, Path(slug): Path<String>) -> Result<StatusCode, ApiError> {
+    let current = fetch_article(slug.clone(), None).await?;
+    if current.author.username != auth.username {
+        return Err(ApiError::Forbidden);
+    }
+    crate::models::Article::delete(slug, auth.username).await?;
+    Ok(StatusCode::OK)
+}
+
+#[tracing::instrument(skip(auth))]
+pub async fn favorite(
+    auth: AuthUser,
+    Path(slug): Path<String>,
+) -> ApiResult<ArticleBody<ArticleResponse>> {
+    // Fail with a 404 instead of silently doing nothing
+    fetch_article(slug.clone(), None).await?;
+    crate::components::toggle_fav(slug.clone(), auth.username.clone(), Some(true)).await?;
+
+    let article = fetch_article(slug, Some(auth.username)).await?;
+    Ok(Json(ArticleBody { article }))
+}
+
+#[tracing::instrument(skip(auth))]
+pub async fn unfavorite(
+    auth: AuthUser,
+    Path(slug): Path<String>,
+) -> ApiResult<ArticleBody<ArticleResponse>> {
+    crate::components::toggle_fav(slug.clone(), auth.username.clone(), Some(false)).await?;
+
+    let article = fetch_article(slug, Some(auth.username)).await?;
+    Ok(Json(ArticleBody { article }))
+}
+
+#[tracing::instrument]
+pub async fn tags() -> ApiResult<TagList> {
+    let tags = sqlx::query!(
+        "SELECT DISTINCT tag FROM ArticleTags WHERE article_id IN (SELECT id FROM Articles WHERE status='published')"
+    )
+        .map(|x| x.tag)
+        .fetch_all(crate::database::get_db())
+        .await?;
+    Ok(Json(TagList { tags }))
+}
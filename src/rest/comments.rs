@@ -0,0 +1,112 @@
+use axum::{Json, extract::Path, http::StatusCode};
+use serde::{Deserialize, Serialize};
+
+use super::{ApiError, ApiResult, AuthUser, profiles::Profile};
+
+#[derive(Deserialize, Serialize)]
+pub struct CommentBody<T> {
+    comment: T,
+}
+
+#[derive(Serialize)]
+pub struct CommentList {
+    comments: Vec<CommentResponse>,
+}
+
+#[derive(Deserialize)]
+pub struct NewComment {
+    body: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommentResponse {
+    id: i32,
+    body: String,
+    created_at: String,
+    updated_at: String,
+    author: Profile,
+}
+
+async fn fetch_comments(
+    slug: String,
+    id: Option<i32>,
+    logged_user: Option<String>,
+) -> Result<Vec<CommentResponse>, sqlx::Error> {
+    sqlx::query!(
+        "
+SELECT
+    c.id, c.body, c.created_at, c.edited_at,
+    COALESCE(u.username, '[deleted]') as \"username!\", u.bio, u.image,
+    EXISTS(SELECT 1 FROM Follows as f JOIN Users as me ON me.id = f.follower_id WHERE me.username=$3 and f.influencer_id=u.id) as following
+FROM Comments as c
+    JOIN Articles as a ON a.id=c.article_id
+    LEFT JOIN Users as u ON u.id=c.user_id
+WHERE a.slug=$1 AND ($2::int IS NULL OR c.id=$2) AND c.deleted_at IS NULL
+ORDER BY c.created_at",
+        slug,
+        id,
+        logged_user,
+    )
+    .map(|x| CommentResponse {
+        id: x.id,
+        body: x.body,
+        created_at: super::format_date(x.created_at),
+        updated_at: super::format_date(x.edited_at.unwrap_or(x.created_at)),
+        author: Profile {
+            username: x.username,
+            bio: x.bio,
+            image: x.image,
+            following: x.following.unwrap_or_default(),
+        },
+    })
+    .fetch_all(crate::database::get_db())
+    .await
+}
+
+async fn fetch_comment(
+    slug: String,
+    id: i32,
+    logged_user: Option<String>,
+) -> Result<CommentResponse, ApiError> {
+    fetch_comments(slug, Some(id), logged_user)
+        .await?
+        .pop()
+        .ok_or(ApiError::NotFound)
+}
+
+#[tracing::instrument(skip(auth))]
+pub async fn list(auth: Option<AuthUser>, Path(slug): Path<String>) -> ApiResult<CommentList> {
+    let comments = fetch_comments(slug, None, AuthUser::username(auth.as_ref())).await?;
+    Ok(Json(CommentList { comments }))
+}
+
+#[tracing::instrument(skip(auth, comment))]
+pub async fn create(
+    auth: AuthUser,
+    Path(slug): Path<String>,
+    Json(CommentBody { comment }): Json<CommentBody<NewComment>>,
+) -> ApiResult<CommentBody<CommentResponse>> {
+    if comment.body.trim().is_empty() {
+        return Err(ApiError::Validation("body can't be empty".into()));
+    }
+    let id =
+        crate::models::Comment::insert(slug.clone(), auth.username.clone(), comment.body, None)
+            .await?;
+
+    let comment = fetch_comment(slug, id, Some(auth.username)).await?;
+    Ok(Json(CommentBody { comment }))
+}
+
+#[tracing::instrument(skip(auth))]
+pub async fn delete(
+    auth: AuthUser,
+    Path((slug, id)): Path<(String, i32)>,
+) -> Result<StatusCode, ApiError> {
+    let comment = fetch_comment(slug, id, None).await?;
+    if comment.author.username != auth.username {
+        return Err(ApiError::Forbidden);
+    }
+    crate::models::Comment::delete(id, auth.username).await?;
+    Ok(StatusCode::OK)
+}
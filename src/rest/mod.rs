@@ -0,0 +1,180 @@
+//! JSON API following the RealWorld (Conduit) spec, so the usual mobile/CLI clients and the
+//! official Postman collection can talk to this backend next to the leptos server functions.
+use axum::{
+    Json, Router,
+    extract::{FromRequestParts, OptionalFromRequestParts},
+    http::{StatusCode, header, request::Parts},
+    response::{IntoResponse, Response},
+    routing::{delete, get, post},
+};
+use serde::Serialize;
+
+mod articles;
+mod comments;
+mod profiles;
+mod users;
+
+pub fn router<S: Clone + Send + Sync + 'static>() -> Router<S> {
+    Router::new()
+        .route("/api/users", post(users::register))
+        .route("/api/users/login", post(users::login))
+        .route("/api/user", get(users::current).put(users::update))
+        .route("/api/profiles/{username}", get(profiles::get_profile))
+        .route(
+            "/api/profiles/{username}/follow",
+            post(profiles::follow).delete(profiles::unfollow),
+        )
+        .route("/api/articles", get(articles::list).post(articles::create))
+        .route("/api/articles/feed", get(articles::feed))
+        .route(
+            "/api/articles/{slug}",
+            get(articles::get_article)
+                .put(articles::update)
+                .delete(articles::delete),
+        )
+        .route(
+            "/api/articles/{slug}/favorite",
+            post(articles::favorite).delete(articles::unfavorite),
+        )
+        .route(
+            "/api/articles/{slug}/comments",
+            get(comments::list).post(comments::create),
+        )
+        .route(
+            "/api/articles/{slug}/comments/{id}",
+            delete(comments::delete),
+        )
+        .route("/api/tags", get(articles::tags))
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+#[derive(Debug)]
+pub(crate) enum ApiError {
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    Validation(String),
+    Internal,
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+    errors: ErrorMessages,
+}
+
+#[derive(Serialize)]
+struct ErrorMessages {
+    body: Vec<String>,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            Self::Unauthorized => (StatusCode::UNAUTHORIZED, "unauthorized".to_string()),
+            Self::Forbidden => (StatusCode::FORBIDDEN, "forbidden".to_string()),
+            Self::NotFound => (StatusCode::NOT_FOUND, "not found".to_string()),
+            Self::Validation(x) => (StatusCode::UNPROCESSABLE_ENTITY, x),
+            Self::Internal => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "There is an unknown problem, try again later".to_string(),
+            ),
+        };
+        let body = ErrorBody {
+            errors: ErrorMessages {
+                body: vec![message],
+            },
+        };
+        (status, Json(body)).into_response()
+    }
+}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(err: sqlx::Error) -> Self {
+        match err {
+            sqlx::Error::RowNotFound => Self::NotFound,
+            sqlx::Error::Database(x) if x.is_unique_violation() => {
+                Self::Validation(match x.constraint() {
+                    Some("users_email_key") => "email has already been taken".into(),
+                    Some("users_username_key") => "username has already been taken".into(),
+                    Some("articles_slug_key") => "slug has already been taken".into(),
+                    _ => "has already been taken".into(),
+                })
+            }
+            x => {
+                tracing::error!("error from DB in the REST API: {x:?}");
+                Self::Internal
+            }
+        }
+    }
+}
+
+impl From<crate::routes::UpdateError> for ApiError {
+    fn from(err: crate::routes::UpdateError) -> Self {
+        match err {
+            // Only the editor sends the version it started from
+            crate::routes::UpdateError::Conflict { .. } => {
+                Self::Validation("the article was modified in the meantime".into())
+            }
+            crate::routes::UpdateError::Published => {
+                Self::Validation("the article is already published".into())
+            }
+            crate::routes::UpdateError::Database(x) => x.into(),
+        }
+    }
+}
+
+/// User authenticated through the `Authorization: Token <jwt>` header.
+pub(crate) struct AuthUser {
+    pub username: String,
+    pub token: String,
+}
+
+impl AuthUser {
+    #[inline]
+    fn username(auth: Option<&Self>) -> Option<String> {
+        auth.map(|x| x.username.clone())
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for AuthUser {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        <Self as OptionalFromRequestParts<S>>::from_request_parts(parts, state)
+            .await?
+            .ok_or(ApiError::Unauthorized)
+    }
+}
+
+impl<S: Send + Sync> OptionalFromRequestParts<S> for AuthUser {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        let Some(token) = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|x| x.to_str().ok())
+            .and_then(|x| x.strip_prefix("Token "))
+        else {
+            return Ok(None);
+        };
+        let Ok(jwt) = crate::auth::decode_token(token) else {
+            return Err(ApiError::Unauthorized);
+        };
+        if !crate::auth::Session::is_active(jwt.claims.sid, &jwt.claims.sub).await? {
+            return Err(ApiError::Unauthorized);
+        }
+        Ok(Some(Self {
+            username: jwt.claims.sub,
+            token: token.to_string(),
+        }))
+    }
+}
+
+fn format_date(date: sqlx::types::chrono::DateTime<sqlx::types::chrono::Utc>) -> String {
+    date.to_rfc3339_opts(sqlx::types::chrono::SecondsFormat::Millis, true)
+}
@@ -0,0 +1,84 @@
+use axum::{
+    Json,
+    extract::Path,
+    response::{IntoResponse, Redirect, Response},
+};
+use serde::Serialize;
+
+use super::{ApiError, ApiResult, AuthUser};
+
+#[derive(Serialize)]
+pub struct ProfileBody {
+    profile: Profile,
+}
+
+#[derive(Serialize)]
+pub struct Profile {
+    pub username: String,
+    pub bio: Option<String>,
+    pub image: Option<String>,
+    pub following: bool,
+}
+
+impl Profile {
+    async fn load(username: String, logged_user: Option<String>) -> Result<Self, sqlx::Error> {
+        sqlx::query!(
+            "
+SELECT
+    u.username, u.bio, u.image,
+    EXISTS(
+        SELECT 1 FROM Follows as f JOIN Users as me ON me.id = f.follower_id
+        WHERE me.username=$2 and f.influencer_id=u.id
+    ) as following
+FROM Users as u WHERE u.username=$1",
+            username,
+            logged_user,
+        )
+        .map(|x| Self {
+            username: x.username,
+            bio: x.bio,
+            image: x.image,
+            following: x.following.unwrap_or_default(),
+        })
+        .fetch_one(crate::database::get_db())
+        .await
+    }
+}
+
+#[tracing::instrument(skip(auth))]
+pub async fn get_profile(
+    auth: Option<AuthUser>,
+    Path(username): Path<String>,
+) -> Result<Response, ApiError> {
+    match Profile::load(username.clone(), AuthUser::username(auth.as_ref())).await {
+        Ok(profile) => Ok(Json(ProfileBody { profile }).into_response()),
+        // Renamed users are still reachable through their old usernames
+        Err(sqlx::Error::RowNotFound) => {
+            match crate::models::User::resolve_alias(username).await? {
+                Some(current) => {
+                    Ok(Redirect::permanent(&format!("/api/profiles/{current}")).into_response())
+                }
+                None => Err(ApiError::NotFound),
+            }
+        }
+        Err(x) => Err(x.into()),
+    }
+}
+
+#[tracing::instrument(skip(auth))]
+pub async fn follow(auth: AuthUser, Path(username): Path<String>) -> ApiResult<ProfileBody> {
+    // Fail with a 404 instead of silently doing nothing
+    Profile::load(username.clone(), None).await?;
+    crate::components::toggle_follow(auth.username.clone(), username.clone(), Some(true)).await?;
+
+    let profile = Profile::load(username, Some(auth.username)).await?;
+    Ok(Json(ProfileBody { profile }))
+}
+
+#[tracing::instrument(skip(auth))]
+pub async fn unfollow(auth: AuthUser, Path(username): Path<String>) -> ApiResult<ProfileBody> {
+    crate::components::toggle_follow(auth.username.clone(), username.clone(), Some(false)).await?;
+
+    let profile = Profile::load(username, Some(auth.username)).await?;
+    Ok(Json(ProfileBody { profile }))
+}
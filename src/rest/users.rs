@@ -0,0 +1,175 @@
+use axum::Json;
+use axum_extra::extract::Host;
+use serde::{Deserialize, Serialize};
+
+use super::{ApiError, ApiResult, AuthUser};
+
+#[derive(Deserialize, Serialize)]
+pub struct UserBody<T> {
+    user: T,
+}
+
+#[derive(Deserialize)]
+pub struct Login {
+    email: String,
+    password: String,
+}
+
+#[derive(Deserialize)]
+pub struct Register {
+    username: String,
+    email: String,
+    password: String,
+}
+
+#[derive(Deserialize)]
+pub struct Update {
+    username: Option<String>,
+    email: Option<String>,
+    password: Option<String>,
+    bio: Option<String>,
+    image: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct UserResponse {
+    email: String,
+    token: String,
+    username: String,
+    bio: Option<String>,
+    image: Option<String>,
+}
+
+impl UserResponse {
+    fn new(user: &crate::models::User, token: String) -> UserBody<Self> {
+        UserBody {
+            user: Self {
+                email: user.email(),
+                token,
+                username: user.username(),
+                bio: user.bio(),
+                image: user.image(),
+            },
+        }
+    }
+}
+
+/// REST clients can't refresh their token, so it lasts as long as the session it belongs to.
+async fn new_token(username: String) -> Result<String, ApiError> {
+    let session = crate::auth::Session::create(username).await?;
+    Ok(crate::auth::new_token(
+        session.username,
+        session.id,
+        crate::auth::SESSION_SECONDS,
+    ))
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn login(
+    Json(UserBody { user }): Json<UserBody<Login>>,
+) -> ApiResult<UserBody<UserResponse>> {
+    let username = sqlx::query_scalar!(
+        "SELECT username FROM Users where email=$1 and password=crypt($2, password)",
+        user.email,
+        user.password,
+    )
+    .fetch_optional(crate::database::get_db())
+    .await?
+    .ok_or(ApiError::Unauthorized)?;
+
+    let user = crate::models::User::get(username.clone()).await?;
+    Ok(Json(UserResponse::new(&user, new_token(username).await?)))
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn register(
+    Host(host): Host,
+    Json(UserBody { user }): Json<UserBody<Register>>,
+) -> ApiResult<UserBody<UserResponse>> {
+    let user = crate::auth::validate_signup(user.username, user.email, user.password)
+        .map_err(ApiError::Validation)?;
+    let mut transaction = crate::database::get_db().begin().await?;
+    user.insert(transaction.as_mut()).await?;
+    crate::auth::EmailVerification::send(
+        transaction.as_mut(),
+        user.username(),
+        user.email(),
+        &host,
+    )
+    .await?;
+    transaction.commit().await?;
+    crate::mailer::wake_worker();
+
+    Ok(Json(UserResponse::new(
+        &user,
+        new_token(user.username()).await?,
+    )))
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn current(auth: AuthUser) -> ApiResult<UserBody<UserResponse>> {
+    let user = crate::models::User::get(auth.username).await?;
+    Ok(Json(UserResponse::new(&user, auth.token)))
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn update(
+    auth: AuthUser,
+    Host(host): Host,
+    Json(UserBody { user: changes }): Json<UserBody<Update>>,
+) -> ApiResult<UserBody<UserResponse>> {
+    let mut user = crate::models::User::get(auth.username.clone()).await?;
+    let previous_email = user.email();
+    if let Some(username) = changes.username {
+        user = user.set_username(username).map_err(ApiError::Validation)?;
+    }
+    if let Some(email) = changes.email {
+        user = user.set_email(email).map_err(ApiError::Validation)?;
+    }
+    if let Some(password) = changes.password {
+        user = user.set_password(password).map_err(ApiError::Validation)?;
+    }
+    if let Some(bio) = changes.bio {
+        user = user.set_bio(bio).map_err(ApiError::Validation)?;
+    }
+    if let Some(image) = changes.image {
+        user = user.set_image(image).map_err(ApiError::Validation)?;
+    }
+    let renamed = user.username() != auth.username;
+    let mut transaction = crate::database::get_db().begin().await?;
+    if renamed
+        && !crate::models::User::rename(transaction.as_mut(), &auth.username, &user.username())
+            .await?
+    {
+        return Err(ApiError::Validation(format!(
+            "username can be changed once every {} days",
+            crate::models::User::RENAME_COOLDOWN_DAYS
+        )));
+    }
+    user.update(transaction.as_mut()).await?;
+    let email_changed = user.email() != previous_email;
+    if email_changed {
+        crate::auth::EmailVerification::send(
+            transaction.as_mut(),
+            user.username(),
+            user.email(),
+            &host,
+        )
+        .await?;
+    }
+    if renamed {
+        // The tokens out there carry the old username
+        crate::auth::Session::revoke_all(transaction.as_mut(), user.username()).await?;
+    }
+    transaction.commit().await?;
+    if email_changed {
+        crate::mailer::wake_worker();
+    }
+
+    let token = if renamed {
+        new_token(user.username()).await?
+    } else {
+        auth.token
+    };
+    Ok(Json(UserResponse::new(&user, token)))
+}
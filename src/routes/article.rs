@@ -1,5 +1,5 @@
 use leptos::prelude::*;
-use leptos_meta::Title;
+use leptos_meta::{Link, Title};
 use leptos_router::{components::A, hooks::use_params_map};
 
 use crate::components::ArticleMeta;
@@ -8,14 +8,19 @@ use crate::components::ArticleMeta;
 pub struct ArticleResult {
     pub(super) article: crate::models::Article,
     pub(super) logged_user: Option<crate::models::User>,
+    /// Absolute URL of the article, search engines take it as the canonical one.
+    pub(super) canonical: String,
 }
 
 #[server(GetArticleAction, "/api", "GetJson")]
 #[tracing::instrument]
 pub async fn get_article(slug: String) -> Result<Option<ArticleResult>, ServerFnError> {
-    let article = match crate::models::Article::for_article(slug).await {
+    let article = match crate::models::Article::for_article(slug.clone()).await {
         Ok(article) => article,
-        Err(sqlx::Error::RowNotFound) => return Ok(None),
+        Err(sqlx::Error::RowNotFound) => match get_renamed_article(slug).await? {
+            Some(article) => article,
+            None => return Ok(None),
+        },
         Err(x) => {
             let err = format!("Error while getting article: {x:?}");
             tracing::error!("{err}");
@@ -25,12 +30,41 @@ pub async fn get_article(slug: String) -> Result<Option<ArticleResult>, ServerFn
         }
     };
 
+    let host = leptos_axum::extract::<axum_extra::extract::Host>()
+        .await
+        .map(|x| x.0)
+        .unwrap_or_else(|_| crate::mailer::site_host());
     Ok(Some(ArticleResult {
+        canonical: crate::mailer::site_link(&host, &format!("/article/{}", article.slug)),
         article,
         logged_user: crate::auth::current_user().await.ok(),
     }))
 }
 
+/// Old slugs of renamed articles redirect to the current one.
+#[cfg(feature = "ssr")]
+async fn get_renamed_article(
+    slug: String,
+) -> Result<Option<crate::models::Article>, ServerFnError> {
+    let article = match crate::models::Article::resolve_alias(slug).await {
+        Ok(Some(canonical)) => crate::models::Article::for_article(canonical).await,
+        Ok(None) => return Ok(None),
+        Err(x) => Err(x),
+    };
+    match article {
+        Ok(article) => {
+            super::redirect_permanently(&format!("/article/{}", article.slug));
+            Ok(Some(article))
+        }
+        Err(x) => {
+            tracing::error!("Error while getting renamed article: {x:?}");
+            Err(ServerFnError::new(
+                "Could not retrieve article, try again later",
+            ))
+        }
+    }
+}
+
 #[tracing::instrument]
 #[component]
 pub fn Article(username: crate::auth::UsernameSignal) -> impl IntoView {
@@ -54,7 +88,9 @@ pub fn Article(username: crate::auth::UsernameSignal) -> impl IntoView {
                         x.map(move |article_result| match article_result {
                             Some(article_result) => {
                                 title.set(article_result.article.slug.clone());
+                                let canonical = article_result.canonical.clone();
                                 view! {
+                                    <Link rel="canonical" href=canonical />
                                     <ArticlePage username result=article_result />
                                 }
                                 .into_any()
@@ -83,6 +119,7 @@ fn ArticleNotFound() -> impl IntoView {
 #[component]
 fn ArticlePage(username: crate::auth::UsernameSignal, result: ArticleResult) -> impl IntoView {
     let article_signal = RwSignal::new(result.article.clone());
+    provide_context(crate::live::subscribe(result.article.slug.clone()));
     let user_signal = RwSignal::new(result.logged_user);
     let tag_list = result.article.tag_list;
 
@@ -97,9 +134,7 @@ fn ArticlePage(username: crate::auth::UsernameSignal, result: ArticleResult) ->
 
             <div class="container page">
                 <div class="row article-content">
-                    <div class="col-md-12">
-                        <p>{result.article.body}</p>
-                    </div>
+                    <div class="col-md-12" inner_html=result.article.body_html.unwrap_or_default()></div>
                 </div>
 
                 <ul class="tag-list">
@@ -128,12 +163,16 @@ fn ArticlePage(username: crate::auth::UsernameSignal, result: ArticleResult) ->
 
 #[server(PostCommentAction, "/api")]
 #[tracing::instrument]
-pub async fn post_comment(slug: String, body: String) -> Result<(), ServerFnError> {
+pub async fn post_comment(
+    slug: String,
+    body: String,
+    parent_id: Option<i32>,
+) -> Result<(), ServerFnError> {
     let Some(logged_user) = crate::auth::get_username() else {
         return Err(ServerFnError::ServerError("you must be logged in".into()));
     };
 
-    crate::models::Comment::insert(slug, logged_user, body)
+    crate::models::Comment::insert(slug, logged_user, body, parent_id)
         .await
         .map(|_| ())
         .map_err(|x| {
@@ -143,14 +182,24 @@ pub async fn post_comment(slug: String, body: String) -> Result<(), ServerFnErro
         })
 }
 
+/// Threads of comments loaded at once, "Load more" brings the following ones.
+const COMMENTS_PAGE: i64 = 20;
+
 #[server(GetCommentsAction, "/api", "GetJson")]
 #[tracing::instrument]
-pub async fn get_comments(slug: String) -> Result<Vec<crate::models::Comment>, ServerFnError> {
-    crate::models::Comment::get_all(slug).await.map_err(|x| {
-        let err = format!("Error while posting a comment: {x:?}");
-        tracing::error!("{err}");
-        ServerFnError::ServerError("Could not post a comment, try again later".into())
-    })
+pub async fn get_comments(
+    slug: String,
+    newest: bool,
+    after: Option<String>,
+) -> Result<crate::models::Page<crate::models::Comment>, ServerFnError> {
+    let after = after.and_then(|x| x.parse().ok());
+    crate::models::Comment::get_page(slug, newest, COMMENTS_PAGE, after)
+        .await
+        .map_err(|x| {
+            let err = format!("Error while getting the comments: {x:?}");
+            tracing::error!("{err}");
+            ServerFnError::ServerError("Could not get the comments, try again later".into())
+        })
 }
 
 #[server(DeleteCommentsAction, "/api")]
@@ -170,6 +219,30 @@ pub async fn delete_comment(id: i32) -> Result<(), ServerFnError> {
         })
 }
 
+#[server(EditCommentAction, "/api")]
+#[tracing::instrument]
+pub async fn edit_comment(id: i32, body: String) -> Result<(), ServerFnError> {
+    let Some(logged_user) = crate::auth::get_username() else {
+        return Err(ServerFnError::ServerError("you must be logged in".into()));
+    };
+    if body.trim().is_empty() {
+        return Err(ServerFnError::new("the comment can't be empty"));
+    }
+
+    match crate::models::Comment::update(id, logged_user, body).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(ServerFnError::new(
+            "the comment doesn't exist or belongs to somebody else",
+        )),
+        Err(x) => {
+            tracing::error!("Error while editing a comment: {x:?}");
+            Err(ServerFnError::ServerError(
+                "Could not edit the comment, try again later".into(),
+            ))
+        }
+    }
+}
+
 #[component]
 fn CommentSection(
     username: crate::auth::UsernameSignal,
@@ -179,13 +252,34 @@ fn CommentSection(
     let comments_action = ServerAction::<PostCommentAction>::new();
     let result = comments_action.version();
     let reset_comment = RwSignal::new("");
-    let comments = Resource::new(
-        move || (result.get(), article.with(|a| a.slug.clone())),
-        move |(_, a)| async move {
-            reset_comment.set("");
-            get_comments(a).await.unwrap_or_else(|_| vec![])
-        },
-    );
+    let slug = Memo::new(move |_| article.with(|a| a.slug.clone()));
+    let newest = RwSignal::new(false);
+    let pages = RwSignal::new(1);
+    // Bumped by the comments of the other readers, so every page loaded is brought up to date
+    let live_version = RwSignal::new(0);
+    let version = Signal::derive(move || result.get() + live_version.get());
+    if let Some(live) = use_context::<crate::live::LiveSignal>() {
+        Effect::new(move |_| {
+            if let Some(crate::live::LiveEvent::Comments {
+                article: slug,
+                count,
+            }) = live.get()
+                && slug == article.with_untracked(|x| x.slug.clone())
+            {
+                article.update(|x| x.comments_count = count);
+                live_version.update(|x| *x += 1);
+            }
+        });
+    }
+
+    Effect::new(move |_| {
+        result.track();
+        reset_comment.set("");
+    });
+    let sort = move |by_newest: bool| {
+        newest.set(by_newest);
+        pages.set(1);
+    };
 
     view! {
         <div class="col-xs-12 col-md-8 offset-md-2">
@@ -205,36 +299,97 @@ fn CommentSection(
                 </ActionForm>
                 </div>
             </Show>
-            <Suspense fallback=move || view! {<p>"Loading Comments from the article"</p> }>
-                <ErrorBoundary fallback=|_| {
-                    view! { <p class="error-messages text-xs-center">"Something went wrong."</p>}
-                }>
-                    {move || comments.get().map(move |c| {
-                        view! {
-                            <For each=move || c.clone().into_iter()
-                                key=|comment| comment.id
-                                children=move |comment: crate::models::Comment| {
-                                    let comment = RwSignal::new(comment);
-                                    view!{<Comment username comment comments />}
-                                }/>
-                        }
-                    })}
-                </ErrorBoundary>
-            </Suspense>
+            <div class="comments-sort">
+                <span>{move || article.with(|x| x.comments_count)}" comments"</span>
+                <button class="btn btn-sm" class:btn-outline-primary=move || !newest.get() on:click=move |_| sort(false)>
+                    "Oldest"
+                </button>
+                <button class="btn btn-sm" class:btn-outline-primary=move || newest.get() on:click=move |_| sort(true)>
+                    "Newest"
+                </button>
+            </div>
+            <CommentPage username article slug newest version pages index=0 after=None />
         </div>
     }
 }
 
+/// Threads following the `after` cursor, the next page goes below once "Load more" shows it.
+#[component]
+fn CommentPage(
+    username: crate::auth::UsernameSignal,
+    article: crate::components::ArticleSignal,
+    slug: Memo<String>,
+    newest: RwSignal<bool>,
+    #[prop(into)] version: Signal<usize>,
+    /// Pages shown, this one is number `index` + 1.
+    pages: RwSignal<usize>,
+    index: usize,
+    after: Option<String>,
+) -> impl IntoView {
+    let comments = Resource::new(
+        move || (version.get(), slug.get(), newest.get(), after.clone()),
+        move |(_, slug, newest, after)| async move {
+            let page = get_comments(slug, newest, after).await;
+            // Every page brings the count, so it follows the replies and deletions
+            if let Ok(page) = page.as_ref() {
+                article.update(|x| x.comments_count = page.total);
+            }
+            page.unwrap_or_default()
+        },
+    );
+
+    view! {
+        <Suspense fallback=move || view! {<p>"Loading Comments from the article"</p> }>
+            <ErrorBoundary fallback=|_| {
+                view! { <p class="error-messages text-xs-center">"Something went wrong."</p>}
+            }>
+                {move || comments.get().map(move |page| {
+                    let next_cursor = page.next_cursor.clone();
+                    view! {
+                        <For each=move || page.items.clone().into_iter()
+                            key=|comment| comment.id
+                            children=move |comment: crate::models::Comment| {
+                                let comment = RwSignal::new(comment);
+                                view!{<Comment username comment comments depth=0 />}
+                            }/>
+                        {next_cursor.map(|after| view! {
+                            <Show
+                                when=move || pages.get() > index + 1
+                                fallback=move || view! {
+                                    <button class="btn btn-sm btn-outline-secondary" on:click=move |_| pages.set(index + 2)>
+                                        "Load more"
+                                    </button>
+                                }>
+                                <CommentPage username article slug newest version pages index=index + 1 after=Some(after.clone()) />
+                            </Show>
+                        }.into_any())}
+                    }
+                })}
+            </ErrorBoundary>
+        </Suspense>
+    }
+}
+
 #[component]
 fn Comment(
     username: crate::auth::UsernameSignal,
     comment: RwSignal<crate::models::Comment>,
-    comments: Resource<Vec<crate::models::Comment>>,
+    comments: Resource<crate::models::Page<crate::models::Comment>>,
+    depth: usize,
 ) -> impl IntoView {
     let user_link = move || format!("/profile/{}", comment.with(|x| x.username.clone()));
     let user_image = move || comment.with(|x| x.user_image.clone().unwrap_or_default());
+    let deleted = move || comment.with(|x| x.deleted);
     let delete_c = ServerAction::<DeleteCommentsAction>::new();
     let delete_result = delete_c.value();
+    let reply_c = ServerAction::<PostCommentAction>::new();
+    let reply_result = reply_c.value();
+    let replying = RwSignal::new(false);
+    let edit_c = ServerAction::<EditCommentAction>::new();
+    let edit_result = edit_c.value();
+    let editing = RwSignal::new(false);
+    let is_author =
+        move || username.get().unwrap_or_default() == comment.with(|x| x.username.clone());
 
     Effect::new(move |_| {
         if let Some(Ok(())) = delete_result.get() {
@@ -242,12 +397,52 @@ fn Comment(
             comments.refetch();
         }
     });
+    Effect::new(move |_| {
+        if let Some(Ok(())) = reply_result.get() {
+            replying.set(false);
+            comments.refetch();
+        }
+    });
+    Effect::new(move |_| {
+        if let Some(Ok(())) = edit_result.get() {
+            editing.set(false);
+            comments.refetch();
+        }
+    });
 
     view! {
         <div class="card">
-            <div class="card-block">
-                <p class="card-text">{move || comment.with(|x| x.body.clone())}</p>
-            </div>
+            <Show
+                when=move || !deleted()
+                fallback=|| view! {
+                    <div class="card-block">
+                        <p class="card-text comment-deleted">"[deleted]"</p>
+                    </div>
+                }>
+            <Show
+                when=move || editing.get()
+                fallback=move || view! {
+                    <div class="card-block">
+                        <p class="card-text">{move || comment.with(|x| x.body.clone())}</p>
+                    </div>
+                }>
+                <ActionForm action=edit_c>
+                    <input type="hidden" name="id" value=move || comment.with(|x| x.id) />
+                    <div class="card-block">
+                        <textarea name="body" class="form-control" rows="3">{comment.with_untracked(|x| x.body.clone())}</textarea>
+                        {move || edit_result.get().and_then(Result::err).map(|x| view! {
+                            <p class="error-messages">{x.to_string()}</p>
+                        })}
+                    </div>
+                    <div class="card-footer">
+                        <button class="btn btn-sm btn-primary" type="submit">"Save"</button>
+                        " "
+                        <button class="btn btn-sm btn-outline-secondary" type="button" on:click=move |_| editing.set(false)>
+                            "Cancel"
+                        </button>
+                    </div>
+                </ActionForm>
+            </Show>
             <div class="card-footer">
                 <A href=user_link><span class="comment-author">
                     <img src=user_image class="comment-author-img" />
@@ -255,9 +450,13 @@ fn Comment(
                 " "
                 <A href=user_link><span  class="comment-author">{move || comment.with(|x| x.username.clone())}</span></A>
                 <span class="date-posted">{move || comment.with(|x| x.created_at.clone())}</span>
-                <Show
-                    when=move || {username.get().unwrap_or_default() == comment.with(|x| x.username.clone())}
-                    fallback=|| ()>
+                {move || comment.with(|x| x.edited_at.clone()).map(|edited_at| view! {
+                    <span class="date-posted">"edited "{edited_at}</span>
+                })}
+                <Show when=is_author fallback=|| ()>
+                    <button class="btn btn-sm" on:click=move |_| editing.update(|x| *x = !*x)>
+                        <i class="ion-edit"></i>
+                    </button>
                     <div  class="comment-author">
                     <ActionForm action=delete_c>
                         <input type="hidden" name="id" value=move || comment.with(|x| x.id) />
@@ -265,7 +464,37 @@ fn Comment(
                     </ActionForm>
                     </div>
                 </Show>
+                <Show
+                    when=move || username.with(Option::is_some) && depth < crate::models::Comment::MAX_DEPTH
+                    fallback=|| ()>
+                    <button class="btn btn-sm" on:click=move |_| replying.update(|x| *x = !*x)>
+                        <i class="ion-reply"></i>" Reply"
+                    </button>
+                </Show>
+            </div>
+            </Show>
+        </div>
+        <Show when=move || replying.get() fallback=|| ()>
+            <div class="card comment-form comment-replies">
+            <ActionForm action=reply_c>
+                <input name="slug" type="hidden" value=move || comment.with(|x| x.article.clone()) />
+                <input name="parent_id" type="hidden" value=move || comment.with(|x| x.id) />
+                <div class="card-block">
+                    <textarea name="body" class="form-control" placeholder="Write a reply..." rows="2"></textarea>
+                </div>
+                <div class="card-footer">
+                    <button class="btn btn-sm btn-primary" type="submit">
+                        "Post Reply"
+                    </button>
+                </div>
+            </ActionForm>
             </div>
+        </Show>
+        <div class="comment-replies">
+            {move || comment.with(|x| x.replies.clone()).into_iter().map(|reply| {
+                let reply = RwSignal::new(reply);
+                view! { <Comment username comment=reply comments depth=depth + 1 /> }.into_any()
+            }).collect_view()}
         </div>
     }
 }
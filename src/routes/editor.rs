@@ -2,29 +2,154 @@ use leptos::prelude::*;
 use leptos_meta::Title;
 use leptos_router::hooks::use_params_map;
 
+use super::DiffLines;
+#[cfg(feature = "ssr")]
+use sqlx::types::chrono::{DateTime, NaiveDateTime, TimeDelta, Utc};
+
 #[derive(serde::Deserialize, Clone, serde::Serialize)]
 pub enum EditorResponse {
     ValidationError(String),
     UpdateError,
+    /// Somebody saved the article after the editor loaded it, `diff` has their changes up to
+    /// `revision` and sending `latest` as the version overwrites them.
+    Conflict {
+        latest: i64,
+        revision: Option<i64>,
+        diff: Vec<crate::models::DiffLine>,
+    },
     Successful(String),
 }
 
+/// Why an article couldn't be saved.
+#[cfg(feature = "ssr")]
+#[derive(Debug)]
+pub(crate) enum UpdateError {
+    /// It was saved after the version the edit started from, `latest` is the current one.
+    /// Only the author of the article gets it.
+    Conflict {
+        latest: i64,
+    },
+    /// Published articles can't go back to drafts or wait for a schedule.
+    Published,
+    Database(sqlx::Error),
+}
+
+#[cfg(feature = "ssr")]
+impl From<sqlx::Error> for UpdateError {
+    fn from(err: sqlx::Error) -> Self {
+        Self::Database(err)
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl std::fmt::Display for UpdateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Conflict { latest } => {
+                write!(f, "the article was saved again as version {latest}")
+            }
+            Self::Published => write!(f, "the article is already published"),
+            Self::Database(x) => x.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl std::error::Error for UpdateError {}
+
 #[cfg_attr(feature = "hydrate", allow(dead_code))]
 #[derive(Debug)]
-struct ArticleUpdate {
+pub(crate) struct ArticleUpdate {
     title: String,
     description: String,
     body: String,
     tag_list: std::collections::HashSet<String>,
 }
 
+/// When the article becomes public, the REST API always publishes right away.
+#[cfg(feature = "ssr")]
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Publication {
+    Draft,
+    Now,
+    At(DateTime<Utc>),
+}
+
+#[cfg(feature = "ssr")]
+impl Publication {
+    /// From the button used to submit the editor, scheduling also needs the `publish_at` input
+    /// and the `tz_offset` of the browser, in minutes from its local time to UTC.
+    fn parse(
+        publication: Option<&str>,
+        publish_at: Option<&str>,
+        tz_offset: Option<i32>,
+    ) -> Result<Self, String> {
+        match publication.unwrap_or("published") {
+            "draft" => Ok(Self::Draft),
+            "scheduled" => {
+                let tz_offset = tz_offset.unwrap_or_default();
+                if tz_offset.abs() > MAX_TZ_OFFSET {
+                    return Err("The time zone of the scheduled date is not valid".into());
+                }
+                let date = publish_at
+                    .and_then(|x| NaiveDateTime::parse_from_str(x, PUBLISH_AT_FORMAT).ok())
+                    .ok_or("You need to provide a date to schedule the article")?
+                    .and_utc()
+                    + TimeDelta::minutes(tz_offset.into());
+                if date <= Utc::now() {
+                    return Err("The scheduled date must be in the future".into());
+                }
+                Ok(Self::At(date))
+            }
+            _ => Ok(Self::Now),
+        }
+    }
+
+    /// The one the article already has, from its stored status and publication date.
+    pub(crate) fn current(status: &str, published_at: Option<DateTime<Utc>>) -> Self {
+        match (status, published_at) {
+            ("published", _) => Self::Now,
+            ("scheduled", Some(date)) => Self::At(date),
+            _ => Self::Draft,
+        }
+    }
+
+    /// Status and publication date stored in `Articles`, articles published right away get
+    /// the time of the transaction like `updated_at`.
+    fn columns(self) -> (&'static str, Option<DateTime<Utc>>) {
+        match self {
+            Self::Draft => ("draft", None),
+            Self::Now => ("published", None),
+            Self::At(date) => ("scheduled", Some(date)),
+        }
+    }
+}
+
 const TITLE_MIN_LENGTH: usize = 4;
 const DESCRIPTION_MIN_LENGTH: usize = 4;
 const BODY_MIN_LENGTH: usize = 10;
+/// Value of a `datetime-local` input, in the local time of the browser.
+#[cfg(feature = "ssr")]
+const PUBLISH_AT_FORMAT: &str = "%Y-%m-%dT%H:%M";
+/// Time zones go from UTC-12 to UTC+14.
+#[cfg(feature = "ssr")]
+const MAX_TZ_OFFSET: i32 = 14 * 60;
+
+/// Minutes from the local time of the browser to UTC at the `datetime-local` value, the
+/// server only sees UTC.
+#[cfg(feature = "hydrate")]
+fn timezone_offset(date: &str) -> i32 {
+    crate::timezoneOffset(date)
+}
+
+#[cfg(not(feature = "hydrate"))]
+fn timezone_offset(_date: &str) -> i32 {
+    0
+}
 
 #[cfg(feature = "ssr")]
 #[tracing::instrument]
-fn validate_article(
+pub(crate) fn validate_article(
     title: String,
     description: String,
     body: String,
@@ -56,71 +181,144 @@ fn validate_article(
     })
 }
 
+/// Saves the article, a new one when `slug` is empty. Edits of an existing one can pass the
+/// `version` they started from to fail instead of overwriting whatever was saved after it.
 #[cfg(feature = "ssr")]
 #[tracing::instrument]
-async fn update_article(
+pub(crate) async fn update_article(
     author: String,
     slug: String,
     article: ArticleUpdate,
-) -> Result<String, sqlx::Error> {
+    publication: Publication,
+    version: Option<i64>,
+) -> Result<String, UpdateError> {
     static BIND_LIMIT: usize = 65535;
+    let (status, published_at) = publication.columns();
     let mut transaction = crate::database::get_db().begin().await?;
-    let (rows_affected, slug) = if slug.is_empty() {
-        // The slug is derived from the title
-        let slug = article
-            .title
-            .chars()
-            .map(|c| {
-                let c = c.to_ascii_lowercase();
-                if c == ' ' { '-' } else { c }
-            })
-            .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
-            .collect::<String>();
-        (sqlx::query!(
-            "INSERT INTO Articles(slug, title, description, body, author) VALUES ($1, $2, $3, $4, $5)",
+    let (id, new_slug) = if slug.is_empty() {
+        let slug = crate::slug::unique(transaction.as_mut(), &article.title, None).await?;
+        (
+            sqlx::query_scalar!(
+                "
+INSERT INTO Articles(slug, title, description, body, status, published_at, live_at, author_id)
+SELECT $1, $2, $3, $4, $5, CASE WHEN $5='published' THEN NOW() ELSE $6 END,
+    CASE WHEN $5='published' THEN NOW() END, id
+FROM Users WHERE username=$7
+RETURNING id",
+                slug,
+                article.title,
+                article.description,
+                article.body,
+                status,
+                published_at,
+                author
+            )
+            .fetch_optional(transaction.as_mut())
+            .await?,
             slug,
-            article.title,
-            article.description,
-            article.body,
-            author
         )
-        .execute(transaction.as_mut())
-        .await?.rows_affected(),
-        slug)
     } else {
+        // A new title may bring a new slug
+        let new_slug =
+            crate::slug::unique(transaction.as_mut(), &article.title, Some(&slug)).await?;
+        // Articles that were already published keep their original date, and `updated_at`
+        // only moves when the content does
         (
-            sqlx::query!(
-                "UPDATE Articles SET title=$1, description=$2, body=$3 WHERE slug=$4 and author=$5",
+            sqlx::query_scalar!(
+                "
+UPDATE Articles SET slug=$1, title=$2, description=$3, body=$4, status=$7,
+    published_at=CASE
+        WHEN $7!='published' THEN $8
+        WHEN status='published' THEN published_at
+        ELSE NOW()
+    END,
+    live_at=CASE
+        WHEN status='published' THEN live_at
+        WHEN $7='published' THEN NOW()
+    END,
+    updated_at=CASE
+        WHEN (title, description, body) IS DISTINCT FROM ($2, $3, $4) THEN NOW()
+        ELSE updated_at
+    END,
+    version=version+1
+WHERE slug=$5 and author_id=(SELECT id FROM Users WHERE username=$6)
+    and ($9::bigint IS NULL OR version=$9) and ($7='published' OR status!='published')
+RETURNING id",
+                new_slug,
                 article.title,
                 article.description,
                 article.body,
                 slug,
                 author,
+                status,
+                published_at,
+                version,
             )
-            .execute(transaction.as_mut())
-            .await?
-            .rows_affected(),
-            slug.clone(),
+            .fetch_optional(transaction.as_mut())
+            .await?,
+            new_slug,
         )
     };
-    if rows_affected != 1 {
-        // We are going to modify just one row, otherwise something funky is going on
+    if id.is_none()
+        && !slug.is_empty()
+        && let Some(current) = sqlx::query!(
+            "SELECT version, status FROM Articles WHERE slug=$1 and author_id=(SELECT id FROM Users WHERE username=$2)",
+            slug,
+            author,
+        )
+        .fetch_optional(transaction.as_mut())
+        .await?
+    {
+        // Only the author gets this far, nobody else may learn about the changes
+        if current.status == "published" && status != "published" {
+            return Err(UpdateError::Published);
+        }
+        if version.is_some() {
+            return Err(UpdateError::Conflict {
+                latest: current.version,
+            });
+        }
+    }
+    let Some(id) = id else {
+        // The article doesn't exist or it belongs to somebody else
         tracing::error!("no rows affected");
-        return Err(sqlx::Error::RowNotFound);
+        return Err(sqlx::Error::RowNotFound.into());
+    };
+    if !slug.is_empty() && slug != new_slug {
+        // The article may be getting back one of its old slugs
+        sqlx::query!("DELETE FROM SlugAliases WHERE slug=$1", new_slug)
+            .execute(transaction.as_mut())
+            .await?;
+        sqlx::query!(
+            "INSERT INTO SlugAliases(slug, article_id) VALUES ($1, $2)",
+            slug,
+            id,
+        )
+        .execute(transaction.as_mut())
+        .await?;
     }
-    sqlx::query!("DELETE FROM ArticleTags WHERE article=$1", slug)
+    let slug = new_slug;
+    sqlx::query!("DELETE FROM ArticleTags WHERE article_id=$1", id)
         .execute(transaction.as_mut())
         .await?;
     if !article.tag_list.is_empty() {
-        let mut qb = sqlx::QueryBuilder::new("INSERT INTO ArticleTags(article, tag) ");
+        let mut qb = sqlx::QueryBuilder::new("INSERT INTO ArticleTags(article_id, tag) ");
         qb.push_values(
             article.tag_list.clone().into_iter().take(BIND_LIMIT / 2),
             |mut b, tag| {
-                b.push_bind(slug.clone()).push_bind(tag);
+                b.push_bind(id).push_bind(tag);
             },
         );
         qb.build().execute(transaction.as_mut()).await?;
     }
+    crate::models::Revision::insert(
+        transaction.as_mut(),
+        id,
+        &article.title,
+        &article.description,
+        &article.body,
+    )
+    .await?;
 
     transaction.commit().await?;
     Ok(slug)
@@ -134,6 +332,11 @@ pub async fn editor_action(
     body: String,
     tag_list: String,
     slug: String,
+    publication: Option<String>,
+    publish_at: Option<String>,
+    tz_offset: Option<i32>,
+    version: Option<i64>,
+    revision: Option<i64>,
 ) -> Result<EditorResponse, ServerFnError> {
     let Some(author) = crate::auth::get_username() else {
         leptos_axum::redirect("/login");
@@ -145,11 +348,58 @@ pub async fn editor_action(
         Ok(x) => x,
         Err(x) => return Ok(EditorResponse::ValidationError(x)),
     };
-    match update_article(author, slug, article).await {
+    let publication =
+        match Publication::parse(publication.as_deref(), publish_at.as_deref(), tz_offset) {
+            Ok(x) => x,
+            Err(x) => return Ok(EditorResponse::ValidationError(x)),
+        };
+    match crate::models::User::get(author.clone()).await {
+        // Drafts stay private, so they can be written before verifying the email
+        Ok(_) if matches!(publication, Publication::Draft) => {}
+        Ok(user) if user.email_verified() => {}
+        Ok(_) => {
+            return Ok(EditorResponse::ValidationError(
+                "you need to verify your email before publishing, check your inbox or the settings"
+                    .to_string(),
+            ));
+        }
+        Err(x) => {
+            tracing::error!("EDITOR ERROR: {}", x.to_string());
+            return Ok(EditorResponse::UpdateError);
+        }
+    }
+    match update_article(author.clone(), slug.clone(), article, publication, version).await {
         Ok(x) => {
             leptos_axum::redirect(&format!("/article/{x}"));
             Ok(EditorResponse::Successful(x))
         }
+        Err(UpdateError::Conflict { latest }) => {
+            // The revisions may belong to a draft, only its author can read them
+            let current = match crate::models::Article::for_article(slug.clone()).await {
+                Ok(x) if x.author.username == author => x,
+                _ => {
+                    tracing::error!(
+                        "EDITOR ERROR: conflict reported to somebody else than the author"
+                    );
+                    return Ok(EditorResponse::UpdateError);
+                }
+            };
+            // What changed from the revision the editor started from
+            let old = crate::models::Revision::text(slug.clone(), revision.unwrap_or_default())
+                .await?
+                .unwrap_or_default();
+            let new = crate::models::Revision::text(slug, current.revision.unwrap_or_default())
+                .await?
+                .unwrap_or_default();
+            Ok(EditorResponse::Conflict {
+                latest,
+                revision: current.revision,
+                diff: crate::diff::lines(&old, &new),
+            })
+        }
+        Err(UpdateError::Published) => Ok(EditorResponse::ValidationError(
+            "Published articles can't go back to drafts or be scheduled".to_string(),
+        )),
         Err(x) => {
             tracing::error!("EDITOR ERROR: {}", x.to_string());
             Ok(EditorResponse::UpdateError)
@@ -169,6 +419,19 @@ pub fn Editor() -> impl IntoView {
         })
     };
 
+    // Saving again after a conflict overwrites the changes it showed
+    let conflict = move || {
+        result.with(|x| match x {
+            Some(Ok(EditorResponse::Conflict {
+                latest, revision, ..
+            })) => Some((*latest, *revision)),
+            _ => None,
+        })
+    };
+
+    // The browser knows the time zone of the scheduled date, it's set once it's picked
+    let tz_offset = RwSignal::new(0);
+
     let params = use_params_map();
     let article_res = Resource::new(
         move || params.get(),
@@ -204,6 +467,9 @@ pub fn Editor() -> impl IntoView {
                                     Ok(EditorResponse::UpdateError) => {
                                         "Error while updating the article, please, try again later".into()
                                     }
+                                    Ok(EditorResponse::Conflict { .. }) => {
+                                        "Somebody saved this article after you opened it, check their changes below and save again to overwrite them".into()
+                                    }
                                     Ok(EditorResponse::Successful(_)) => {
                                         String::new()
                                     }
@@ -212,6 +478,14 @@ pub fn Editor() -> impl IntoView {
                             })}
                         </strong>
                     </p>
+                    {move || result.with(|x| match x {
+                        Some(Ok(EditorResponse::Conflict { diff, .. })) => Some(view! {
+                            <div class="col-md-10 offset-md-1 col-xs-12">
+                                <DiffLines diff=diff.clone() />
+                            </div>
+                        }),
+                        _ => None,
+                    })}
 
                     <div class="col-md-10 offset-md-1 col-xs-12">
                         <ActionForm action=editor_server_action>
@@ -220,7 +494,13 @@ pub fn Editor() -> impl IntoView {
                                 view! { <p class="error-messages text-xs-center">"Something went wrong."</p>}
                             }>
                                 {move || article_res.get().map(move |x| x.map(move |a| {
+                                    let scheduled = (a.article.status == crate::models::ArticleStatus::Scheduled)
+                                        .then(|| format!("Scheduled for {} (UTC)", a.article.created_at));
+                                    // Published articles stay published, they can only be edited
+                                    let published = !a.article.slug.is_empty()
+                                        && a.article.status == crate::models::ArticleStatus::Published;
                                     view! {
+                                        {scheduled.map(|x| view! {<p class="text-xs-center">{x}</p>})}
                                         <fieldset>
                                             <fieldset class="form-group">
                                                 <input name="title" type="text" class="form-control form-control-lg" minlength=TITLE_MIN_LENGTH
@@ -240,10 +520,28 @@ pub fn Editor() -> impl IntoView {
                                                 <input name="tag_list" type="text" class="form-control"
                                                     placeholder="Enter tags(space separated)" value=a.article.tag_list.join(" ") />
                                             </fieldset>
+                                            {(!published).then(|| view! {
+                                                <fieldset class="form-group">
+                                                    <label for="publish_at">"Publication date, to schedule the article"</label>
+                                                    <input id="publish_at" name="publish_at" type="datetime-local" class="form-control"
+                                                        on:input=move |ev| tz_offset.set(timezone_offset(&event_target_value(&ev))) />
+                                                    <input name="tz_offset" type="hidden" value=move || tz_offset.get() />
+                                                </fieldset>
+                                            })}
                                             <input name="slug" type="hidden" value=a.article.slug />
-                                            <button class="btn btn-lg pull-xs-right btn-primary" type="submit">
+                                            <input name="version" type="hidden" value=move || conflict().map(|x| x.0).or(a.article.version) />
+                                            <input name="revision" type="hidden" value=move || conflict().and_then(|x| x.1).or(a.article.revision) />
+                                            <button class="btn btn-lg pull-xs-right btn-primary" type="submit" name="publication" value="published">
                                                 "Publish Article"
                                             </button>
+                                            {(!published).then(|| view! {
+                                                <button class="btn btn-lg pull-xs-right btn-outline-primary" type="submit" name="publication" value="scheduled">
+                                                    "Schedule"
+                                                </button>
+                                                <button class="btn btn-lg btn-outline-secondary" type="submit" name="publication" value="draft">
+                                                    "Save draft"
+                                                </button>
+                                            })}
                                         </fieldset>
                                     }
                                 }))}
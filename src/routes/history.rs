@@ -0,0 +1,281 @@
+use leptos::prelude::*;
+use leptos_meta::Title;
+use leptos_router::{
+    components::{A, Form},
+    hooks::{use_params_map, use_query_map},
+};
+
+use crate::models::{DiffLine, Revision};
+
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+pub struct HistoryResult {
+    slug: String,
+    title: String,
+    revisions: Vec<Revision>,
+    from: i64,
+    to: i64,
+    diff: Vec<DiffLine>,
+    /// Only the author can restore the old revisions.
+    can_restore: bool,
+    /// Version of the article the page shows, restoring fails if it was saved again since.
+    version: i64,
+}
+
+#[server(GetHistoryAction, "/api", "GetJson")]
+#[tracing::instrument]
+pub async fn get_history(
+    slug: String,
+    from: Option<i64>,
+    to: Option<i64>,
+) -> Result<Option<HistoryResult>, ServerFnError> {
+    // The history is as visible as the article itself
+    let history = match crate::models::Article::for_article(slug).await {
+        Ok(article) => load_history(article, from, to).await,
+        Err(sqlx::Error::RowNotFound) => return Ok(None),
+        Err(x) => Err(x),
+    };
+    history.map(Some).map_err(|x| {
+        tracing::error!("Error while getting the article history: {x:?}");
+        ServerFnError::new("Could not retrieve the history, try again later")
+    })
+}
+
+#[cfg(feature = "ssr")]
+async fn load_history(
+    article: crate::models::Article,
+    from: Option<i64>,
+    to: Option<i64>,
+) -> Result<HistoryResult, sqlx::Error> {
+    let revisions = Revision::get_all(article.slug.clone()).await?;
+    // The latest edit by default
+    let to = to
+        .or_else(|| revisions.first().map(|x| x.id))
+        .unwrap_or_default();
+    let from = from
+        .or_else(|| revisions.get(1).map(|x| x.id))
+        .unwrap_or(to);
+    let old = Revision::text(article.slug.clone(), from)
+        .await?
+        .unwrap_or_default();
+    let new = Revision::text(article.slug.clone(), to)
+        .await?
+        .unwrap_or_default();
+
+    Ok(HistoryResult {
+        can_restore: crate::auth::get_username().as_ref() == Some(&article.author.username),
+        version: article.version.unwrap_or_default(),
+        diff: crate::diff::lines(&old, &new),
+        slug: article.slug,
+        title: article.title,
+        revisions,
+        from,
+        to,
+    })
+}
+
+#[server(RestoreRevisionAction, "/api")]
+#[tracing::instrument]
+pub async fn restore_revision(slug: String, id: i64, version: i64) -> Result<(), ServerFnError> {
+    let Some(author) = crate::auth::get_username() else {
+        return Err(ServerFnError::ServerError("you must be logged in".into()));
+    };
+    let internal_error = |x: &dyn std::fmt::Debug| {
+        tracing::error!("Error while restoring a revision: {x:?}");
+        ServerFnError::ServerError("Could not restore the revision, try again later".into())
+    };
+
+    let Some((article, publication)) = load_revision(&author, &slug, id)
+        .await
+        .map_err(|x| internal_error(&x))?
+    else {
+        return Err(ServerFnError::new(
+            "the revision doesn't exist or the article belongs to somebody else",
+        ));
+    };
+    // Like in the editor, only drafts can be saved before verifying the email
+    if !matches!(publication, super::Publication::Draft) {
+        let user = crate::models::User::get(author.clone())
+            .await
+            .map_err(|x| internal_error(&x))?;
+        if !user.email_verified() {
+            return Err(ServerFnError::new(
+                "you need to verify your email before publishing, check your inbox or the settings",
+            ));
+        }
+    }
+    let slug = match super::update_article(author, slug, article, publication, Some(version)).await
+    {
+        Ok(x) => x,
+        Err(super::UpdateError::Conflict { .. }) => {
+            return Err(ServerFnError::new(
+                "the article was saved again after loading its history, reload it to restore the revision",
+            ));
+        }
+        Err(x) => return Err(internal_error(&x)),
+    };
+    leptos_axum::redirect(&format!("/article/{slug}"));
+    Ok(())
+}
+
+/// The content of the revision to save as a new edit, the tags and the publication stay as
+/// they are. `None` if it isn't a revision of an article of the author.
+#[cfg(feature = "ssr")]
+async fn load_revision(
+    author: &str,
+    slug: &str,
+    id: i64,
+) -> Result<Option<(super::ArticleUpdate, super::Publication)>, String> {
+    let Some(revision) = sqlx::query!(
+        "
+SELECT
+    r.title, r.description, r.body, a.status, a.published_at,
+    (SELECT string_agg(tag, ' ') FROM ArticleTags WHERE article_id = a.id) as tag_list
+FROM ArticleRevisions as r
+    JOIN Articles as a ON a.id = r.article_id
+    JOIN Users as u ON u.id = a.author_id
+WHERE r.id=$1 and a.slug=$2 and u.username=$3",
+        id,
+        slug,
+        author,
+    )
+    .fetch_optional(crate::database::get_db())
+    .await
+    .map_err(|x| x.to_string())?
+    else {
+        return Ok(None);
+    };
+
+    let article = super::validate_article(
+        revision.title,
+        revision.description,
+        revision.body,
+        &revision.tag_list.unwrap_or_default(),
+    )?;
+    let publication = super::Publication::current(&revision.status, revision.published_at);
+    Ok(Some((article, publication)))
+}
+
+#[tracing::instrument]
+#[component]
+pub fn ArticleHistory() -> impl IntoView {
+    let params = use_params_map();
+    let query = use_query_map();
+    let revision = move |name: &str| query.with(|x| x.get(name).and_then(|x| x.parse().ok()));
+    let history = Resource::new(
+        move || {
+            (
+                params.with(|x| x.get("slug").unwrap_or_default()),
+                revision("from"),
+                revision("to"),
+            )
+        },
+        |(slug, from, to)| async move { get_history(slug, from, to).await },
+    );
+
+    view! {
+        <Title text="Article history"/>
+        <div class="container page">
+            <Suspense fallback=move || view! { <p>"Loading History"</p> }>
+                <ErrorBoundary fallback=|_| {
+                    view! { <p class="error-messages text-xs-center">"Something went wrong, please try again later."</p>}
+                }>
+                    {move || history.get().map(move |x| x.map(move |history| match history {
+                        Some(history) => view! { <HistoryPage history /> }.into_any(),
+                        None => view! {
+                            <p class="error-messages text-xs-center">"Article not found."</p>
+                        }
+                        .into_any(),
+                    }))}
+                </ErrorBoundary>
+            </Suspense>
+        </div>
+    }
+}
+
+#[component]
+pub fn DiffLines(diff: Vec<DiffLine>) -> impl IntoView {
+    view! {
+        <pre class="diff">
+            {diff.into_iter().map(|line| match line {
+                DiffLine::Same(x) => view! { <div>"  "{x}</div> }.into_any(),
+                DiffLine::Added(x) => view! { <div class="diff-added">"+ "{x}</div> }.into_any(),
+                DiffLine::Removed(x) => view! { <div class="diff-removed">"- "{x}</div> }.into_any(),
+            }).collect_view()}
+        </pre>
+    }
+}
+
+fn revision_options(revisions: &[Revision], selected: i64) -> impl IntoView + use<> {
+    revisions
+        .iter()
+        .map(|x| {
+            view! {
+                <option value=x.id selected=x.id == selected>
+                    {format!("{} - {}", x.created_at, x.title)}
+                </option>
+            }
+        })
+        .collect_view()
+}
+
+#[component]
+fn HistoryPage(history: HistoryResult) -> impl IntoView {
+    let restore = ServerAction::<RestoreRevisionAction>::new();
+    let HistoryResult {
+        slug,
+        title,
+        revisions,
+        from,
+        to,
+        diff,
+        can_restore,
+        version,
+    } = history;
+    let latest = revisions.first().map(|x| x.id);
+
+    view! {
+        <h1><A href=format!("/article/{slug}")>{title}</A></h1>
+        <Form action=format!("/article/{slug}/history")>
+            <fieldset class="form-group">
+                <label for="from">"From"</label>
+                <select id="from" name="from" class="form-control">{revision_options(&revisions, from)}</select>
+            </fieldset>
+            <fieldset class="form-group">
+                <label for="to">"To"</label>
+                <select id="to" name="to" class="form-control">{revision_options(&revisions, to)}</select>
+            </fieldset>
+            <button class="btn btn-primary" type="submit">"Compare"</button>
+        </Form>
+
+        <DiffLines diff />
+
+        <h4>"Revisions"</h4>
+        {move || restore.value().with(|x| match x {
+            Some(Err(x)) => Some(view! { <p class="error-messages">{format!("Problem while restoring: {x}")}</p> }),
+            _ => None,
+        })}
+        <ul class="list-unstyled">
+            {revisions.into_iter().map(|revision| {
+                let slug = slug.clone();
+                let restorable = can_restore && Some(revision.id) != latest;
+                view! {
+                    <li>
+                        <span class="date">{revision.created_at}</span>" "{revision.title}" "
+                        {restorable.then(|| view! {
+                            <div class="inline">
+                                <ActionForm action=restore>
+                                    <input type="hidden" name="slug" value=slug />
+                                    <input type="hidden" name="id" value=revision.id />
+                                    <input type="hidden" name="version" value=version />
+                                    <button type="submit" class="btn btn-sm btn-outline-secondary">
+                                        <i class="ion-reply"></i>" Restore"
+                                    </button>
+                                </ActionForm>
+                            </div>
+                        })}
+                    </li>
+                }
+            }).collect_view()}
+        </ul>
+    }
+}
@@ -6,15 +6,17 @@ use crate::components::ArticlePreviewList;
 
 #[server(HomeAction, "/api", "GetJson")]
 async fn home_articles(
-    page: u32,
     amount: u32,
     tag: String,
     my_feed: bool,
-) -> Result<Vec<crate::models::Article>, ServerFnError> {
-    let page = i64::from(page);
+    after: Option<String>,
+    before: Option<String>,
+) -> Result<crate::models::Page<crate::models::Article>, ServerFnError> {
     let amount = i64::from(amount);
+    let after = after.and_then(|x| x.parse().ok());
+    let before = before.and_then(|x| x.parse().ok());
 
-    crate::models::Article::for_home_page(page, amount, tag, my_feed)
+    crate::models::Article::for_home_page(amount, tag, my_feed, after, before)
         .await
         .map_err(|x| {
             tracing::error!("problem while fetching home articles: {x:?}");
@@ -24,7 +26,9 @@ async fn home_articles(
 
 #[server(GetTagsAction, "/api", "GetJson")]
 async fn get_tags() -> Result<Vec<String>, ServerFnError> {
-    sqlx::query!("SELECT DISTINCT tag FROM ArticleTags")
+    sqlx::query!(
+        "SELECT DISTINCT tag FROM ArticleTags WHERE article_id IN (SELECT id FROM Articles WHERE status='published')"
+    )
         .map(|x| x.tag)
         .fetch_all(crate::database::get_db())
         .await
@@ -44,13 +48,14 @@ pub fn HomePage(username: crate::auth::UsernameSignal) -> impl IntoView {
         move |pagination| async move {
             tracing::debug!("making another request: {pagination:?}");
             home_articles(
-                pagination.get_page(),
                 pagination.get_amount(),
                 pagination.get_tag().to_string(),
                 pagination.get_my_feed(),
+                pagination.get_after(),
+                pagination.get_before(),
             )
             .await
-            .unwrap_or_else(|_| vec![])
+            .unwrap_or_default()
         },
     );
 
@@ -134,32 +139,24 @@ pub fn HomePage(username: crate::auth::UsernameSignal) -> impl IntoView {
                     </div>
 
                     <ul class="pagination">
-                        <Show
-                            when=move || {pagination.with(|x| x.as_ref().map(crate::models::Pagination::get_page).unwrap_or_default()) > 0}
-                            fallback=|| ()
-                        >
-                            <li class="page-item">
-                                <a class="btn btn-primary" href=move || pagination.get().unwrap_or_default().previous_page().to_string()>
-                                    "<< Previous page"
-                                </a>
-                            </li>
-                        </Show>
                         <Suspense fallback=|| ()>
-                            <Show
-                                // TODO: fix this dummy logic
-                                when=move || {
-                                    let n_articles = articles.with(|x| x.as_ref().map_or(0, std::vec::Vec::len));
-                                    n_articles > 0 && n_articles >=
-                                    pagination.with(|x| x.as_ref().map(crate::models::Pagination::get_amount).unwrap_or_default()) as usize
-                                }
-                                fallback=|| ()
-                            >
+                            {move || articles.with(|x| x.as_ref().and_then(|x| x.prev_cursor.clone())).map(|cursor| view! {
+                                <li class="page-item">
+                                    <a class="btn btn-primary" href=move || pagination.get().unwrap_or_default().previous_page(&cursor).to_string()>
+                                        "<< Previous page"
+                                    </a>
+                                </li>
+                            })}
+                            {move || articles.with(|x| x.as_ref().and_then(|x| x.next_cursor.clone())).map(|cursor| view! {
                                 <li class="page-item">
-                                    <a class="btn btn-primary" href=move || pagination.get().unwrap_or_default().next_page().to_string()>
+                                    <a class="btn btn-primary" href=move || pagination.get().unwrap_or_default().next_page(&cursor).to_string()>
                                         "Next page >>"
                                     </a>
                                 </li>
-                            </Show>
+                            })}
+                            <li class="page-item">
+                                {move || articles.with(|x| x.as_ref().map(|x| format!("{} articles", x.total)))}
+                            </li>
                         </Suspense>
                     </ul>
                 </div>
@@ -192,7 +189,7 @@ fn TagList() -> impl IntoView {
                             let same = tag == tag_elected;
                             view!{
                                 <a class="tag-pill tag-default" class:tag-primary=same
-                                    href=move || pagination.get().unwrap_or_default().set_tag(if same {""} else {&tag_href}).to_string()>
+                                    href=move || pagination.get().unwrap_or_default().reset_page().set_tag(if same {""} else {&tag_href}).to_string()>
                                     {tag}
                                 </a>
                             }
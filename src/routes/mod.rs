@@ -1,17 +1,48 @@
 pub use article::*;
 pub use editor::*;
+pub use history::*;
 pub use home::*;
 pub use login::*;
+pub use notifications::*;
 pub use profile::*;
 pub use reset_password::*;
+pub use search::*;
 pub use settings::*;
 pub use signup::*;
+pub use unsubscribe::*;
+pub use verify_email::*;
 
 mod article;
 mod editor;
+mod history;
 mod home;
 mod login;
+mod notifications;
 mod profile;
 mod reset_password;
+mod search;
 mod settings;
 mod signup;
+mod unsubscribe;
+mod verify_email;
+
+/// Like `leptos_axum::redirect`, but browsers and crawlers get a `301 Moved Permanently` so
+/// they replace the old URL.
+#[cfg(feature = "ssr")]
+pub(crate) fn redirect_permanently(path: &str) {
+    leptos_axum::redirect(path);
+    // Server function calls from the client are redirected by the router instead
+    let accepts_html =
+        leptos::prelude::use_context::<axum::http::request::Parts>().is_some_and(|parts| {
+            parts
+                .headers
+                .get(axum::http::header::ACCEPT)
+                .and_then(|x| x.to_str().ok())
+                .is_some_and(|x| x.contains("text/html"))
+        });
+    if accepts_html
+        && let Some(response) = leptos::prelude::use_context::<leptos_axum::ResponseOptions>()
+    {
+        response.set_status(axum::http::StatusCode::MOVED_PERMANENTLY);
+    }
+}
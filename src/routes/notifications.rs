@@ -0,0 +1,129 @@
+use leptos::prelude::*;
+use leptos_meta::Title;
+use leptos_router::components::A;
+
+use crate::models::{Notification, NotificationKind};
+
+#[server(GetNotificationsAction, "/api", "GetJson")]
+#[tracing::instrument]
+pub async fn get_notifications() -> Result<Vec<Notification>, ServerFnError> {
+    let Some(username) = crate::auth::get_username() else {
+        leptos_axum::redirect("/login");
+        return Err(ServerFnError::ServerError("you must be logged in".into()));
+    };
+    Notification::get_all(username).await.map_err(|x| {
+        tracing::error!("problem while getting the notifications: {x:?}");
+        ServerFnError::ServerError("Could not get the notifications, try again later".into())
+    })
+}
+
+#[server(UnreadNotificationsAction, "/api", "GetJson")]
+#[tracing::instrument]
+pub async fn unread_notifications() -> Result<i64, ServerFnError> {
+    let Some(username) = crate::auth::get_username() else {
+        return Ok(0);
+    };
+    Notification::unread_count(username).await.map_err(|x| {
+        tracing::error!("problem while counting the notifications: {x:?}");
+        ServerFnError::ServerError("Could not count the notifications".into())
+    })
+}
+
+/// Marks the notification as read, or all of them without `id`.
+#[server(MarkNotificationsReadAction, "/api")]
+#[tracing::instrument]
+pub async fn mark_notifications_read(id: Option<i64>) -> Result<(), ServerFnError> {
+    let Some(username) = crate::auth::get_username() else {
+        return Err(ServerFnError::ServerError("you must be logged in".into()));
+    };
+    Notification::mark_read(username, id).await.map_err(|x| {
+        tracing::error!("problem while marking the notifications as read: {x:?}");
+        ServerFnError::ServerError("Could not update the notifications, try again later".into())
+    })
+}
+
+#[tracing::instrument]
+#[component]
+pub fn Notifications(mark_read: ServerAction<MarkNotificationsReadAction>) -> impl IntoView {
+    let version = mark_read.version();
+    let notifications = Resource::new(move || version.get(), |_| get_notifications());
+
+    view! {
+        <Title text="Notifications"/>
+        <div class="container page">
+            <div class="row">
+                <div class="col-md-8 offset-md-2 col-xs-12">
+                    <h1>"Notifications"</h1>
+                    <ActionForm action=mark_read>
+                        <button type="submit" class="btn btn-sm btn-outline-secondary">
+                            <i class="ion-checkmark-round"></i>" Mark all as read"
+                        </button>
+                    </ActionForm>
+                    <Suspense fallback=move || view! { <p>"Loading Notifications"</p> }>
+                        <ErrorBoundary fallback=|_| {
+                            view! { <p class="error-messages text-xs-center">"Something went wrong, please try again later."</p>}
+                        }>
+                            {move || notifications.get().map(move |x| x.map(move |notifications| {
+                                if notifications.is_empty() {
+                                    return view! { <p>"Nothing new."</p> }.into_any();
+                                }
+                                view! {
+                                    <ul class="list-unstyled notifications">
+                                        {notifications.into_iter().map(|notification| view! {
+                                            <NotificationItem notification mark_read />
+                                        }).collect_view()}
+                                    </ul>
+                                }
+                                .into_any()
+                            }))}
+                        </ErrorBoundary>
+                    </Suspense>
+                </div>
+            </div>
+        </div>
+    }
+}
+
+#[component]
+fn NotificationItem(
+    notification: Notification,
+    mark_read: ServerAction<MarkNotificationsReadAction>,
+) -> impl IntoView {
+    let Notification {
+        id,
+        kind,
+        actor,
+        actor_image,
+        article,
+        article_title,
+        created_at,
+        read,
+    } = notification;
+    let action = match kind {
+        NotificationKind::Follow => " started following you",
+        NotificationKind::Favorite => " favorited ",
+        NotificationKind::Comment => " commented on ",
+    };
+
+    view! {
+        <li class:notification-unread=!read>
+            <A href=format!("/profile/{actor}")>
+                <img src=actor_image.unwrap_or_default() class="comment-author-img" />
+                " "{actor.clone()}
+            </A>
+            {action}
+            {article.map(|slug| view! {
+                <A href=format!("/article/{slug}")>{article_title.unwrap_or(slug)}</A>
+            })}
+            " "<span class="date-posted">{created_at}</span>
+            {(!read).then(|| view! {
+                <div class="inline">
+                    <ActionForm action=mark_read>
+                        <input type="hidden" name="id" value=id />
+                        <button type="submit" class="btn btn-sm">"Mark as read"</button>
+                    </ActionForm>
+                </div>
+            })}
+        </li>
+    }
+}
@@ -1,6 +1,6 @@
 use leptos::prelude::*;
 use leptos_meta::Title;
-use leptos_router::hooks::{use_params_map, use_query_map};
+use leptos_router::hooks::{use_params_map, use_query, use_query_map};
 
 use crate::components::ArticlePreviewList;
 use crate::components::ButtonFollow;
@@ -10,14 +10,28 @@ use crate::components::ButtonFollow;
 pub async fn profile_articles(
     username: String,
     favourites: Option<bool>,
-) -> Result<Vec<crate::models::Article>, ServerFnError> {
-    crate::models::Article::for_user_profile(username, favourites.unwrap_or_default())
-        .await
-        .map_err(|x| {
-            let err = format!("Error while getting user_profile articles: {x:?}");
-            tracing::error!("{err}");
-            ServerFnError::ServerError("Could not retrieve articles, try again later".into())
-        })
+    drafts: Option<bool>,
+    amount: u32,
+    after: Option<String>,
+    before: Option<String>,
+) -> Result<crate::models::Page<crate::models::Article>, ServerFnError> {
+    let after = after.and_then(|x| x.parse().ok());
+    let before = before.and_then(|x| x.parse().ok());
+
+    crate::models::Article::for_user_profile(
+        username,
+        favourites.unwrap_or_default(),
+        drafts.unwrap_or_default(),
+        i64::from(amount),
+        after,
+        before,
+    )
+    .await
+    .map_err(|x| {
+        let err = format!("Error while getting user_profile articles: {x:?}");
+        tracing::error!("{err}");
+        ServerFnError::ServerError("Could not retrieve articles, try again later".into())
+    })
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone)]
@@ -31,7 +45,10 @@ pub struct UserProfileModel {
 pub async fn user_profile(username: String) -> Result<Option<UserProfileModel>, ServerFnError> {
     let user = match crate::models::User::get(username.clone()).await {
         Ok(user) => user,
-        Err(sqlx::Error::RowNotFound) => return Ok(None),
+        Err(sqlx::Error::RowNotFound) => match get_renamed_user(username).await? {
+            Some(user) => user,
+            None => return Ok(None),
+        },
         Err(x) => {
             let err = format!("Error while getting user in user_profile: {x:?}");
             tracing::error!("{err}");
@@ -43,8 +60,12 @@ pub async fn user_profile(username: String) -> Result<Option<UserProfileModel>,
 
     match crate::auth::get_username() {
         Some(lu) => sqlx::query!(
-            "SELECT EXISTS(SELECT * FROM Follows WHERE follower=$2 and influencer=$1)",
-            username,
+            "
+SELECT EXISTS(
+    SELECT 1 FROM Follows as f, Users as fu, Users as iu
+    WHERE f.follower_id=fu.id and f.influencer_id=iu.id and fu.username=$2 and iu.username=$1
+)",
+            user.username(),
             lu,
         )
         .fetch_one(crate::database::get_db())
@@ -67,6 +88,28 @@ pub async fn user_profile(username: String) -> Result<Option<UserProfileModel>,
     }
 }
 
+/// Old usernames of renamed users redirect to the current one.
+#[cfg(feature = "ssr")]
+async fn get_renamed_user(username: String) -> Result<Option<crate::models::User>, ServerFnError> {
+    let user = match crate::models::User::resolve_alias(username).await {
+        Ok(Some(current)) => crate::models::User::get(current).await,
+        Ok(None) => return Ok(None),
+        Err(x) => Err(x),
+    };
+    match user {
+        Ok(user) => {
+            super::redirect_permanently(&format!("/profile/{}", user.username()));
+            Ok(Some(user))
+        }
+        Err(x) => {
+            tracing::error!("Error while getting renamed user: {x:?}");
+            Err(ServerFnError::ServerError(
+                "Could not retrieve profile, try again later".into(),
+            ))
+        }
+    }
+}
+
 #[allow(clippy::redundant_closure)]
 #[tracing::instrument]
 #[component]
@@ -75,9 +118,24 @@ pub fn Profile(username: crate::auth::UsernameSignal) -> impl IntoView {
     let route_user = move || params.with(|x| x.get("user").clone().unwrap_or_default());
     let query = use_query_map();
     let favourite = move || query.with(|x| x.get("favourites").map(|_| true));
+    let drafts = move || query.with(|x| x.get("drafts").map(|_| true));
+    let pagination = use_query::<crate::models::Pagination>();
+    // Drafts are only listed to their author
+    let own_profile = move || username.with(|x| x.as_deref() == Some(route_user().as_str()));
 
     let user_article_href = move || format!("/profile/{}", route_user());
     let favourites_href = move || format!("{}?favourites=true", user_article_href());
+    let drafts_href = move || format!("{}?drafts=true", user_article_href());
+    let page_href = move |pagination: crate::models::Pagination| {
+        let list = if favourite().unwrap_or_default() {
+            "favourites=true&"
+        } else if drafts().unwrap_or_default() {
+            "drafts=true&"
+        } else {
+            ""
+        };
+        format!("{}?{list}{}", user_article_href(), pagination.page_query())
+    };
 
     let profile_info = Resource::new(
         move || route_user(),
@@ -96,8 +154,26 @@ pub fn Profile(username: crate::auth::UsernameSignal) -> impl IntoView {
     };
 
     let articles = Resource::new(
-        move || (favourite(), route_user()),
-        move |(fav, user)| async move { profile_articles(user, fav).await.unwrap_or_else(|_| vec![]) },
+        move || {
+            (
+                favourite(),
+                drafts(),
+                route_user(),
+                pagination.get().unwrap_or_default(),
+            )
+        },
+        move |(fav, drafts, user, pagination)| async move {
+            profile_articles(
+                user,
+                fav,
+                drafts,
+                pagination.get_amount(),
+                pagination.get_after(),
+                pagination.get_before(),
+            )
+            .await
+            .unwrap_or_default()
+        },
     );
 
     view! {
@@ -112,7 +188,8 @@ pub fn Profile(username: crate::auth::UsernameSignal) -> impl IntoView {
                             <ul class="nav nav-pills outline-active">
                                 <li class="nav-item">
                                     <a class="nav-link"
-                                        class:active=move || !favourite().unwrap_or_default() href=user_article_href>
+                                        class:active=move || !favourite().unwrap_or_default() && !drafts().unwrap_or_default()
+                                        href=user_article_href>
                                             {move || route_user()}"'s Articles"
                                         </a>
                                 </li>
@@ -121,10 +198,39 @@ pub fn Profile(username: crate::auth::UsernameSignal) -> impl IntoView {
                                         class:active=move || favourite().unwrap_or_default()
                                         href=favourites_href>"Favorited Articles"</a>
                                 </li>
+                                <Show when=own_profile>
+                                    <li class="nav-item">
+                                        <a class="nav-link"
+                                            class:active=move || drafts().unwrap_or_default()
+                                            href=drafts_href>"Drafts"</a>
+                                    </li>
+                                </Show>
                             </ul>
                         </div>
 
                         <ArticlePreviewList username=username articles=articles />
+
+                        <ul class="pagination">
+                            <Suspense fallback=|| ()>
+                                {move || articles.with(|x| x.as_ref().and_then(|x| x.prev_cursor.clone())).map(|cursor| view! {
+                                    <li class="page-item">
+                                        <a class="btn btn-primary" href=move || page_href(pagination.get().unwrap_or_default().previous_page(&cursor))>
+                                            "<< Previous page"
+                                        </a>
+                                    </li>
+                                })}
+                                {move || articles.with(|x| x.as_ref().and_then(|x| x.next_cursor.clone())).map(|cursor| view! {
+                                    <li class="page-item">
+                                        <a class="btn btn-primary" href=move || page_href(pagination.get().unwrap_or_default().next_page(&cursor))>
+                                            "Next page >>"
+                                        </a>
+                                    </li>
+                                })}
+                                <li class="page-item">
+                                    {move || articles.with(|x| x.as_ref().map(|x| format!("{} articles", x.total)))}
+                                </li>
+                            </Suspense>
+                        </ul>
                     </div>
                 </div>
             </div>
@@ -1,63 +1,39 @@
-use std::env;
-
 use leptos::prelude::*;
 use leptos_meta::Title;
 use leptos_router::{hooks::use_query, params::Params};
 
-#[cfg(feature = "ssr")]
-struct EmailCredentials {
-    email: String,
-    passwd: String,
-    smtp_server: String,
-}
-
-#[cfg(feature = "ssr")]
-static EMAIL_CREDS: std::sync::OnceLock<EmailCredentials> = std::sync::OnceLock::new();
-
 #[tracing::instrument]
 #[server(ResetPasswordAction1, "/api")]
 pub async fn reset_password_1(email: String) -> Result<String, ServerFnError> {
-    if let Err(x) = crate::models::User::get_email(email.clone()).await {
-        let err = format!("Bad email : {x:?}");
-        tracing::error!("{err}");
-    } else {
-        let creds = EMAIL_CREDS.get_or_init(|| EmailCredentials {
-            email: env::var("MAILER_EMAIL").unwrap(),
-            passwd: env::var("MAILER_PASSWD").unwrap(),
-            smtp_server: env::var("MAILER_SMTP_SERVER").unwrap(),
-        });
-        let host = leptos_axum::extract::<axum_extra::extract::Host>().await?.0;
-        let schema = if cfg!(debug_assertions) {
-            "http"
-        } else {
-            "https"
-        };
-        let token = crate::auth::encode_token(&crate::auth::TokenClaims {
-            sub: email.clone(),
-            exp: (sqlx::types::chrono::Utc::now().timestamp() as usize) + 3_600,
-        })
-        .unwrap();
-        let uri = format!("{schema}://{host}/reset_password?token={token}");
-        // Build a simple multipart message
-        let message = mail_send::mail_builder::MessageBuilder::new()
-            .from(("Realworld Leptos", creds.email.as_str()))
-            .to(vec![("You", email.as_str())])
-            .subject("Your password reset from realworld leptos")
-            .text_body(format!(
-                "You can reset your password accessing the following link: {uri}"
-            ));
-
-        // Connect to the SMTP submissions port, upgrade to TLS and
-        // authenticate using the provided credentials.
-        mail_send::SmtpClientBuilder::new(creds.smtp_server.as_str(), 587)
-            .implicit_tls(false)
-            .credentials((creds.email.as_str(), creds.passwd.as_str()))
-            .connect()
-            .await
-            .unwrap()
-            .send(message)
-            .await
-            .unwrap();
+    match crate::models::User::get_email(email.clone()).await {
+        Err(x) => {
+            let err = format!("Bad email : {x:?}");
+            tracing::error!("{err}");
+        }
+        Ok(user) => {
+            let host = leptos_axum::extract::<axum_extra::extract::Host>().await?.0;
+            let queued = async {
+                let mut transaction = crate::database::get_db().begin().await?;
+                let reset =
+                    crate::auth::PasswordReset::create(transaction.as_mut(), user.username())
+                        .await?;
+                let uri = crate::mailer::site_link(
+                    &host,
+                    &format!("/reset_password?token={}", reset.token),
+                );
+                crate::mailer::Email::password_reset(email, &user.username(), &uri)
+                    .enqueue(transaction.as_mut())
+                    .await?;
+                transaction.commit().await
+            };
+            if let Err(x) = queued.await {
+                tracing::error!("problem while issuing the reset token: {x:?}");
+                return Err(ServerFnError::new(
+                    "Could not reset the password, try again later",
+                ));
+            }
+            crate::mailer::wake_worker();
+        }
     }
     return Ok(String::from("Check your email"));
 }
@@ -73,35 +49,31 @@ pub async fn reset_password_2(
     password: String,
     confirm: String,
 ) -> Result<String, ServerFnError> {
-    let mut message = String::from("Something went wrong, try again later");
+    let message = String::from("Something went wrong, try again later");
     if !validate_reset(&password, &confirm) {
         return Ok(message);
     }
-    let Ok(claims) = crate::auth::decode_token(token.as_str()) else {
-        tracing::info!("Invalid token provided");
-        return Ok(message);
-    };
-    let email = claims.claims.sub;
-    let Ok(user) = crate::models::User::get_email(email.clone()).await else {
-        tracing::info!("User does not exist");
-        return Ok(message);
-    };
-    match user.set_password(password) {
-        Ok(u) => {
-            if let Err(error) = u.update().await {
-                tracing::error!(email, ?error, "error while resetting the password");
-            } else {
-                // A real password reset would have a list of issued tokens and invalidation over
-                // the used ones. As this would grow much bigger in complexity, I prefer to write
-                // down this security vulnerability and left it simple :)
-                message = String::from("Password successfully reset, please, proceed to login");
-            }
+    if let Err(x) = crate::models::User::default().set_password(password.clone()) {
+        return Ok(x);
+    }
+    match crate::auth::PasswordReset::reset_password(token, password).await {
+        Ok(Some(username)) => {
+            tracing::info!(username, "password reset");
+            Ok(String::from(
+                "Password successfully reset, please, proceed to login",
+            ))
         }
-        Err(x) => {
-            message = x;
+        Ok(None) => {
+            tracing::info!("Invalid, used or expired token provided");
+            Ok(String::from(
+                "This reset link is not valid anymore, ask for a new one",
+            ))
+        }
+        Err(error) => {
+            tracing::error!(?error, "error while resetting the password");
+            Ok(message)
         }
     }
-    Ok(message)
 }
 
 #[derive(Params, PartialEq)]
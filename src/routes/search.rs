@@ -0,0 +1,64 @@
+use leptos::prelude::*;
+use leptos_meta::Title;
+use leptos_router::hooks::use_query_map;
+
+use crate::components::ArticlePreviewList;
+
+const RESULTS_AMOUNT: i64 = 20;
+
+#[server(SearchAction, "/api", "GetJson")]
+#[tracing::instrument]
+pub async fn search_articles(
+    q: String,
+) -> Result<crate::models::Page<crate::models::Article>, ServerFnError> {
+    if q.trim().is_empty() {
+        return Ok(crate::models::Page::default());
+    }
+    crate::models::Article::search(q, RESULTS_AMOUNT)
+        .await
+        .map_err(|x| {
+            tracing::error!("problem while searching articles: {x:?}");
+            ServerFnError::new("Problem while searching articles")
+        })
+}
+
+#[component]
+pub fn Search(username: crate::auth::UsernameSignal) -> impl IntoView {
+    let query = use_query_map();
+    let q = move || query.with(|x| x.get("q").unwrap_or_default());
+
+    let articles = Resource::new(q, |q| async move {
+        search_articles(q).await.unwrap_or_default()
+    });
+
+    view! {
+        <Title text="Search"/>
+        <div class="home-page">
+            <div class="container page">
+                <div class="row">
+                    <div class="col-md-9">
+                        <div class="feed-toggle">
+                            <ul class="nav nav-pills outline-active">
+                                <li class="nav-item">
+                                    <span class="nav-link active">
+                                        <i class="ion-search"></i>" "{q}
+                                    </span>
+                                </li>
+                            </ul>
+                        </div>
+                        <Suspense fallback=|| ()>
+                            <p>
+                                {move || articles.with(|x| x.as_ref().map(|x| match x.total {
+                                    0 => "No articles found".to_string(),
+                                    total if x.has_more => format!("Showing the {} most relevant of {total} articles", x.items.len()),
+                                    total => format!("{total} articles found"),
+                                }))}
+                            </p>
+                        </Suspense>
+                        <ArticlePreviewList username=username articles=articles/>
+                    </div>
+                </div>
+            </div>
+        </div>
+    }
+}
@@ -14,33 +14,185 @@ pub enum SettingsUpdateError {
 #[server(SettingsUpdateAction, "/api")]
 pub async fn settings_update(
     image: String,
+    username: String,
     bio: String,
     email: String,
     password: String,
     confirm_password: String,
 ) -> Result<SettingsUpdateError, ServerFnError> {
     let user = get_user().await?;
-    let username = user.username();
-    let user = match update_user_validation(user, image, bio, email, password, &confirm_password) {
+    let previous_username = user.username();
+    let previous_email = user.email();
+    let user = match update_user_validation(
+        user,
+        image,
+        username,
+        bio,
+        email,
+        password,
+        &confirm_password,
+    ) {
         Ok(x) => x,
         Err(x) => return Ok(x),
     };
-    user.update()
-        .await
-        .map(|_| SettingsUpdateError::Successful)
-        .map_err(move |x| {
+    let renamed = user.username() != previous_username;
+    let email_changed = user.email() != previous_email;
+    let updated = async {
+        let host = leptos_axum::extract::<axum_extra::extract::Host>().await?.0;
+        let mut transaction = crate::database::get_db().begin().await?;
+        if renamed
+            && !crate::models::User::rename(
+                transaction.as_mut(),
+                &previous_username,
+                &user.username(),
+            )
+            .await?
+        {
+            return Ok(SettingsUpdateError::ValidationError(format!(
+                "You can change your username once every {} days",
+                crate::models::User::RENAME_COOLDOWN_DAYS
+            )));
+        }
+        user.update(transaction.as_mut()).await?;
+        if email_changed {
+            crate::auth::EmailVerification::send(
+                transaction.as_mut(),
+                user.username(),
+                user.email(),
+                &host,
+            )
+            .await?;
+        }
+        if renamed {
+            // The tokens out there carry the old username
+            crate::auth::Session::revoke_all(transaction.as_mut(), user.username()).await?;
+        }
+        transaction.commit().await?;
+        Ok::<_, ServerFnError>(SettingsUpdateError::Successful)
+    };
+    match updated.await {
+        Ok(SettingsUpdateError::Successful) => {
+            if email_changed {
+                crate::mailer::wake_worker();
+            }
+            if renamed {
+                crate::auth::set_username(user.username()).await;
+            }
+            Ok(SettingsUpdateError::Successful)
+        }
+        Ok(x) => Ok(x),
+        Err(x) if x.to_string().contains("users_username_key") => Ok(
+            SettingsUpdateError::ValidationError("This username is already taken".into()),
+        ),
+        Err(x) => {
             tracing::error!(
                 "Problem while updating user: {} with error {}",
-                username,
+                previous_username,
                 x.to_string()
             );
-            ServerFnError::ServerError("Problem while updating user".into())
+            Err(ServerFnError::ServerError(
+                "Problem while updating user".into(),
+            ))
+        }
+    }
+}
+
+/// Returns `false` when the password is wrong.
+#[server(DeleteAccountAction, "/api")]
+#[tracing::instrument(skip(password))]
+pub async fn delete_account(
+    password: String,
+    anonymize_comments: Option<String>,
+) -> Result<bool, ServerFnError> {
+    let user = get_user().await?;
+    let deleted =
+        crate::models::User::delete(user.username(), password, anonymize_comments.is_some())
+            .await
+            .map_err(|x| {
+                tracing::error!("problem while deleting the user: {x:?}");
+                ServerFnError::ServerError("Could not delete the account, try again later".into())
+            })?;
+    // A wrong password is not an error status, the client would fail to read the answer
+    if deleted {
+        crate::auth::remove_username();
+        leptos_axum::redirect("/");
+    }
+    Ok(deleted)
+}
+
+#[tracing::instrument]
+#[server(ResendVerificationAction, "/api")]
+pub async fn resend_verification() -> Result<(), ServerFnError> {
+    let user = get_user().await?;
+    if user.email_verified() {
+        return Ok(());
+    }
+    let host = leptos_axum::extract::<axum_extra::extract::Host>().await?.0;
+    let sent = async {
+        let mut transaction = crate::database::get_db().begin().await?;
+        crate::auth::EmailVerification::send(
+            transaction.as_mut(),
+            user.username(),
+            user.email(),
+            &host,
+        )
+        .await?;
+        transaction.commit().await
+    };
+    sent.await.map_err(|x| {
+        tracing::error!("problem while sending the verification email: {x:?}");
+        ServerFnError::ServerError("Could not send the email, try again later".into())
+    })?;
+    crate::mailer::wake_worker();
+    Ok(())
+}
+
+#[tracing::instrument]
+#[server(NotificationPreferencesGetAction, "/api", "GetJson")]
+pub async fn notification_preferences_get()
+-> Result<crate::models::NotificationPreferences, ServerFnError> {
+    let user = get_user().await?;
+    crate::models::NotificationPreferences::get(user.username())
+        .await
+        .map_err(|x| {
+            tracing::error!("problem while getting the notification preferences: {x:?}");
+            ServerFnError::ServerError("Could not get the preferences, try again later".into())
         })
 }
 
+/// The kinds left unchecked are turned off.
+#[tracing::instrument]
+#[server(NotificationPreferencesUpdateAction, "/api")]
+pub async fn notification_preferences_update(
+    follows: Option<String>,
+    favorites: Option<String>,
+    comments: Option<String>,
+    digest: String,
+) -> Result<(), ServerFnError> {
+    let Some(digest) = crate::models::DigestFrequency::parse(&digest) else {
+        return Err(ServerFnError::ServerError(format!(
+            "Unknown digest frequency {digest}"
+        )));
+    };
+    let user = get_user().await?;
+    crate::models::NotificationPreferences {
+        follows: follows.is_some(),
+        favorites: favorites.is_some(),
+        comments: comments.is_some(),
+        digest,
+    }
+    .update(user.username())
+    .await
+    .map_err(|x| {
+        tracing::error!("problem while updating the notification preferences: {x:?}");
+        ServerFnError::ServerError("Could not update the preferences, try again later".into())
+    })
+}
+
 fn update_user_validation(
     mut user: crate::models::User,
     image: String,
+    username: String,
     bio: String,
     email: String,
     password: String,
@@ -55,7 +207,9 @@ fn update_user_validation(
             .map_err(SettingsUpdateError::ValidationError)?;
     }
 
-    user.set_email(email)
+    user.set_username(username)
+        .map_err(SettingsUpdateError::ValidationError)?
+        .set_email(email)
         .map_err(SettingsUpdateError::ValidationError)?
         .set_bio(bio)
         .map_err(SettingsUpdateError::ValidationError)?
@@ -86,7 +240,12 @@ pub async fn settings_get() -> Result<crate::models::User, ServerFnError> {
 }
 
 #[component]
-pub fn Settings(logout: crate::auth::LogoutSignal) -> impl IntoView {
+pub fn Settings(
+    logout: crate::auth::LogoutSignal,
+    logout_all: crate::auth::LogoutAllSignal,
+    settings: ServerAction<SettingsUpdateAction>,
+    delete_account: ServerAction<DeleteAccountAction>,
+) -> impl IntoView {
     let resource = Resource::new(|| (), move |()| settings_get());
 
     view! {
@@ -102,7 +261,7 @@ pub fn Settings(logout: crate::auth::LogoutSignal) -> impl IntoView {
                             <ErrorBoundary fallback=|_| view!{<p>"There was a problem while fetching settings, try again later"</p>}>
                                 {move || {
                                     resource.get().map(move |x| {
-                                        x.map(move |user| view!{<SettingsViewForm user />})
+                                        x.map(move |user| view!{<SettingsViewForm user settings_server_action=settings />})
                                     })
                                 }}
                             </ErrorBoundary>
@@ -111,6 +270,21 @@ pub fn Settings(logout: crate::auth::LogoutSignal) -> impl IntoView {
                         <ActionForm action=logout>
                             <button type="submit" class="btn btn-outline-danger">"Or click here to logout."</button>
                         </ActionForm>
+                        <ActionForm action=logout_all>
+                            <button type="submit" class="btn btn-outline-danger">"Log out from all devices."</button>
+                        </ActionForm>
+                        <hr />
+                        <NotificationPreferencesForm />
+                        <hr />
+                        <h4>"Your data"</h4>
+                        <p>
+                            "Download your profile, articles, comments, favorites and follows as "
+                            <a href="/settings/export.json" rel="external">"JSON"</a>
+                            " or as a "
+                            <a href="/settings/export.zip" rel="external">"ZIP with your articles in Markdown"</a>
+                            "."
+                        </p>
+                        <DeleteAccount delete_account />
                     </div>
                 </div>
             </div>
@@ -119,8 +293,129 @@ pub fn Settings(logout: crate::auth::LogoutSignal) -> impl IntoView {
 }
 
 #[component]
-fn SettingsViewForm(user: crate::models::User) -> impl IntoView {
-    let settings_server_action = ServerAction::<SettingsUpdateAction>::new();
+fn NotificationPreferencesForm() -> impl IntoView {
+    let update = ServerAction::<NotificationPreferencesUpdateAction>::new();
+    let result = update.value();
+    let preferences = Resource::new(|| (), |()| notification_preferences_get());
+
+    view! {
+        <h4>"Notifications"</h4>
+        <Suspense fallback=|| view!{<p>"Loading notification preferences"</p>}>
+            <ErrorBoundary fallback=|_| view!{<p>"There was a problem while fetching the preferences, try again later"</p>}>
+                {move || preferences.get().map(move |x| x.map(move |preferences| view! {
+                    <ActionForm action=update>
+                        <fieldset class="form-group">
+                            <label>
+                                <input name="follows" type="checkbox" value="true" checked=preferences.follows />
+                                " Someone follows me"
+                            </label>
+                        </fieldset>
+                        <fieldset class="form-group">
+                            <label>
+                                <input name="favorites" type="checkbox" value="true" checked=preferences.favorites />
+                                " Someone favorites one of my articles"
+                            </label>
+                        </fieldset>
+                        <fieldset class="form-group">
+                            <label>
+                                <input name="comments" type="checkbox" value="true" checked=preferences.comments />
+                                " Someone comments on one of my articles"
+                            </label>
+                        </fieldset>
+                        <fieldset class="form-group">
+                            <label for="digest">"Email me the new articles of the authors I follow"</label>
+                            <select id="digest" name="digest" class="form-control">
+                                {crate::models::DigestFrequency::ALL.into_iter().map(|x| view! {
+                                    <option value=x.as_str() selected={x == preferences.digest}>
+                                        {match x {
+                                            crate::models::DigestFrequency::Never => "Never",
+                                            crate::models::DigestFrequency::Daily => "Daily",
+                                            crate::models::DigestFrequency::Weekly => "Weekly",
+                                        }}
+                                    </option>
+                                }).collect_view()}
+                            </select>
+                        </fieldset>
+                        <button type="submit" class="btn btn-outline-primary" disabled=move || update.pending().get()>
+                            "Update notifications"
+                        </button>
+                        {move || result.with(|x| match x {
+                            Some(Ok(())) => " Saved",
+                            Some(Err(_)) => " Could not save the preferences, try again later",
+                            None => "",
+                        })}
+                    </ActionForm>
+                }))}
+            </ErrorBoundary>
+        </Suspense>
+    }
+}
+
+#[component]
+fn DeleteAccount(delete_account: ServerAction<DeleteAccountAction>) -> impl IntoView {
+    let result = delete_account.value();
+
+    view! {
+        <h4 class="text-danger">"Delete your account"</h4>
+        <p>"Your profile, articles, favorites and follows will be gone for good."</p>
+        <p class="error-messages">
+            {move || result.with(|x| match x {
+                Some(Ok(false)) => "The password is not correct".to_string(),
+                Some(Err(x)) => format!("{x:?}"),
+                _ => String::new(),
+            })}
+        </p>
+        <ActionForm action=delete_account>
+            <fieldset class="form-group">
+                <input name="password" class="form-control" type="password" required
+                    placeholder="Your current password" />
+            </fieldset>
+            <fieldset class="form-group">
+                <label>
+                    <input name="anonymize_comments" type="checkbox" value="true" />
+                    " Keep my comments, without my name"
+                </label>
+            </fieldset>
+            <fieldset class="form-group">
+                <label>
+                    <input type="checkbox" required />
+                    " I understand this can't be undone"
+                </label>
+            </fieldset>
+            <button type="submit" class="btn btn-danger" disabled=move || delete_account.pending().get()>
+                "Delete my account"
+            </button>
+        </ActionForm>
+    }
+}
+
+#[component]
+fn EmailVerificationNotice() -> impl IntoView {
+    let resend = ServerAction::<ResendVerificationAction>::new();
+    let result = resend.value();
+
+    view! {
+        <div class="alert alert-warning">
+            "Your email is not verified yet, you can't publish articles until you follow the link we sent you."
+            <ActionForm action=resend>
+                <button type="submit" class="btn btn-sm btn-outline-warning" disabled=move || resend.pending().get()>
+                    "Resend verification email"
+                </button>
+            </ActionForm>
+            {move || result.with(|x| match x {
+                Some(Ok(())) => "Sent, check your inbox",
+                Some(Err(_)) => "Could not send the email, try again later",
+                None => "",
+            })}
+        </div>
+    }
+}
+
+#[component]
+fn SettingsViewForm(
+    user: crate::models::User,
+    settings_server_action: ServerAction<SettingsUpdateAction>,
+) -> impl IntoView {
     let result = settings_server_action.value();
     let error = move || {
         result.with(|x| {
@@ -129,7 +424,12 @@ fn SettingsViewForm(user: crate::models::User) -> impl IntoView {
         })
     };
 
+    let email_verified = user.email_verified();
+
     view! {
+        <Show when=move || !email_verified>
+            <EmailVerificationNotice />
+        </Show>
         <p class="text-xs-center"
             class:text-success=move || !error()
             class:error-messages=error
@@ -157,7 +457,7 @@ fn SettingsViewForm(user: crate::models::User) -> impl IntoView {
             let Ok(data) = SettingsUpdateAction::from_event(&ev) else {
                 return ev.prevent_default();
             };
-            if let Err(x) = update_user_validation(crate::models::User::default(), data.image, data.bio, data.email, data.password, &data.confirm_password) {
+            if let Err(x) = update_user_validation(crate::models::User::default(), data.image, data.username, data.bio, data.email, data.password, &data.confirm_password) {
                 result.set(Some(Ok(x)));
                 ev.prevent_default();
             }
@@ -168,8 +468,11 @@ fn SettingsViewForm(user: crate::models::User) -> impl IntoView {
                         placeholder="URL of profile picture" />
                 </fieldset>
                 <fieldset class="form-group">
-                    <input disabled value=user.username() class="form-control form-control-lg" type="text"
+                    <input name="username" value=user.username() class="form-control form-control-lg" type="text"
                         placeholder="Your Name" />
+                    <small class="form-text text-muted">
+                        {format!("You can change your username once every {} days.", crate::models::User::RENAME_COOLDOWN_DAYS)}
+                    </small>
                 </fieldset>
                 <fieldset class="form-group">
                     <textarea name="bio" class="form-control form-control-lg" rows="8"
@@ -0,0 +1,65 @@
+use leptos::prelude::*;
+use leptos_meta::Title;
+use leptos_router::{hooks::use_query, params::Params};
+
+#[tracing::instrument]
+#[server(UnsubscribeDigestAction, "/api")]
+pub async fn unsubscribe_digest(token: String) -> Result<bool, ServerFnError> {
+    match crate::auth::DigestUnsubscribe::unsubscribe(token).await {
+        Ok(Some(username)) => {
+            tracing::info!(username, "digest unsubscribed");
+            Ok(true)
+        }
+        Ok(None) => {
+            tracing::info!("Invalid unsubscribe token provided");
+            Ok(false)
+        }
+        Err(x) => {
+            tracing::error!("problem while unsubscribing from the digest: {x:?}");
+            Err(ServerFnError::ServerError(
+                "Could not unsubscribe, try again later".into(),
+            ))
+        }
+    }
+}
+
+#[derive(Params, PartialEq)]
+struct TokenQuery {
+    token: Option<String>,
+}
+
+#[component]
+pub fn Unsubscribe() -> impl IntoView {
+    let q = use_query::<TokenQuery>();
+    let result = Resource::new(
+        move || q.with(|x| x.as_ref().ok().and_then(|x| x.token.clone())),
+        |token| async move {
+            match token {
+                Some(token) => unsubscribe_digest(token).await,
+                None => Ok(false),
+            }
+        },
+    );
+
+    view! {
+        <Title text="Unsubscribe"/>
+        <div class="auth-page">
+            <div class="container page">
+                <div class="row">
+                    <div class="col-md-6 offset-md-3 col-xs-12">
+                        <h1 class="text-xs-center">"Unsubscribe"</h1>
+                        <Suspense fallback=move || view! {<p class="text-xs-center">"Unsubscribing"</p>}>
+                            <p class="text-xs-center">
+                                {move || result.get().map(|x| match x {
+                                    Ok(true) => "You won't get more digests, you can subscribe again from your settings",
+                                    Ok(false) => "This unsubscribe link is not valid, you can stop the digests from your settings",
+                                    Err(_) => "There was a problem, try again later",
+                                })}
+                            </p>
+                        </Suspense>
+                    </div>
+                </div>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,65 @@
+use leptos::prelude::*;
+use leptos_meta::Title;
+use leptos_router::{hooks::use_query, params::Params};
+
+#[tracing::instrument]
+#[server(VerifyEmailAction, "/api")]
+pub async fn verify_email(token: String) -> Result<bool, ServerFnError> {
+    match crate::auth::EmailVerification::verify(token).await {
+        Ok(Some(username)) => {
+            tracing::info!(username, "email verified");
+            Ok(true)
+        }
+        Ok(None) => {
+            tracing::info!("Invalid, used or expired token provided");
+            Ok(false)
+        }
+        Err(x) => {
+            tracing::error!("problem while verifying the email: {x:?}");
+            Err(ServerFnError::ServerError(
+                "Could not verify the email, try again later".into(),
+            ))
+        }
+    }
+}
+
+#[derive(Params, PartialEq)]
+struct TokenQuery {
+    token: Option<String>,
+}
+
+#[component]
+pub fn VerifyEmail() -> impl IntoView {
+    let q = use_query::<TokenQuery>();
+    let result = Resource::new(
+        move || q.with(|x| x.as_ref().ok().and_then(|x| x.token.clone())),
+        |token| async move {
+            match token {
+                Some(token) => verify_email(token).await,
+                None => Ok(false),
+            }
+        },
+    );
+
+    view! {
+        <Title text="Verify email"/>
+        <div class="auth-page">
+            <div class="container page">
+                <div class="row">
+                    <div class="col-md-6 offset-md-3 col-xs-12">
+                        <h1 class="text-xs-center">"Verify email"</h1>
+                        <Suspense fallback=move || view! {<p class="text-xs-center">"Verifying your email"</p>}>
+                            <p class="text-xs-center">
+                                {move || result.get().map(|x| match x {
+                                    Ok(true) => "Your email is verified, you can start publishing articles",
+                                    Ok(false) => "This verification link is not valid anymore, ask for a new one from your settings",
+                                    Err(_) => "There was a problem, try again later",
+                                })}
+                            </p>
+                        </Suspense>
+                    </div>
+                </div>
+            </div>
+        </div>
+    }
+}
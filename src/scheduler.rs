@@ -0,0 +1,36 @@
+//! Periodic jobs running in the background of the server, they only touch the database so
+//! running them from several instances at once is harmless.
+use std::time::Duration;
+
+/// Scheduled articles are published at most this late.
+const PUBLISH_INTERVAL: Duration = Duration::from_secs(30);
+/// Digests are sent at most this late.
+const DIGEST_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+pub fn spawn_scheduler() {
+    tokio::spawn(async {
+        let mut interval = tokio::time::interval(PUBLISH_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            match crate::models::Article::publish_scheduled().await {
+                Ok(0) => {}
+                Ok(published) => tracing::info!("published {published} scheduled articles"),
+                Err(x) => tracing::error!("problem while publishing the scheduled articles: {x:?}"),
+            }
+        }
+    });
+    tokio::spawn(async {
+        let host = crate::mailer::site_host();
+        let mut interval = tokio::time::interval(DIGEST_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            match crate::models::Digest::send_due(&host).await {
+                Ok(0) => {}
+                Ok(sent) => tracing::info!("queued {sent} digests"),
+                Err(x) => tracing::error!("problem while sending the digests: {x:?}"),
+            }
+        }
+    });
+}
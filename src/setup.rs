@@ -15,6 +15,10 @@ pub async fn init_app(configuration_path: Option<&str>) {
     crate::database::init_db()
         .await
         .expect("problem during initialization of the database");
+    crate::mailer::init_mailer();
+    crate::mailer::spawn_worker();
+    crate::scheduler::spawn_scheduler();
+    crate::live::spawn_listener();
 
     // Get leptos configuration
     let conf = get_configuration(configuration_path).unwrap();
@@ -30,6 +34,10 @@ pub async fn init_app(configuration_path: Option<&str>) {
             let leptos_options = leptos_options.clone();
             move || shell(leptos_options.clone())
         })
+        .merge(crate::rest::router())
+        .merge(crate::export::router())
+        .merge(crate::feeds::router())
+        .merge(crate::live::router())
         .fallback_service(serve_dir)
         .layer(
             tower_http::trace::TraceLayer::new_for_http()
@@ -0,0 +1,79 @@
+//! Slugs identify articles in their URLs. They are derived from the title, only contain
+//! `[a-z0-9-]` and are never reused: the previous slugs of an article stay in `SlugAliases`
+//! so old links keep working.
+use std::collections::HashSet;
+
+const MAX_LENGTH: usize = 64;
+/// Used when nothing of the title survives, e.g. it's only emojis.
+const FALLBACK: &str = "article";
+
+/// Transliterates the title to ASCII and joins its words with single dashes.
+pub fn slugify(title: &str) -> String {
+    let mut slug = String::with_capacity(title.len());
+    for c in deunicode::deunicode(title).chars() {
+        if slug.len() >= MAX_LENGTH {
+            break;
+        }
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        FALLBACK.to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+/// Whether `slug` is `base` itself or `base` with a collision suffix.
+fn derives_from(slug: &str, base: &str) -> bool {
+    slug == base
+        || slug
+            .strip_prefix(base)
+            .and_then(|x| x.strip_prefix('-'))
+            .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Picks the slug for an article with the given title, adding a numeric suffix if another
+/// article uses it or used it in the past.
+///
+/// `article` is the current slug when the article already exists, it's kept if the title
+/// still produces it.
+#[tracing::instrument(skip(connection))]
+pub async fn unique(
+    connection: &mut sqlx::PgConnection,
+    title: &str,
+    article: Option<&str>,
+) -> Result<String, sqlx::Error> {
+    let base = slugify(title);
+    if let Some(current) = article
+        && derives_from(current, &base)
+    {
+        return Ok(current.to_string());
+    }
+    let taken = sqlx::query_scalar!(
+        "
+SELECT slug FROM Articles WHERE (slug=$1 OR slug LIKE $1 || '-%') AND slug IS DISTINCT FROM $2
+UNION
+SELECT sa.slug FROM SlugAliases as sa JOIN Articles as a ON a.id = sa.article_id
+WHERE (sa.slug=$1 OR sa.slug LIKE $1 || '-%') AND a.slug IS DISTINCT FROM $2",
+        base,
+        article,
+    )
+    .fetch_all(connection)
+    .await?
+    .into_iter()
+    .flatten()
+    .collect::<HashSet<String>>();
+
+    if !taken.contains(&base) {
+        return Ok(base);
+    }
+    Ok((2..)
+        .map(|n| format!("{base}-{n}"))
+        .find(|x| !taken.contains(x))
+        .unwrap_or(base))
+}